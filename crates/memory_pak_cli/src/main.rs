@@ -0,0 +1,598 @@
+//! `memory-pak-cli`: search and edit the collection the desktop app keeps in
+//! `state.json`, from a terminal or a script.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use memory_pak_core::{
+    default_state_path, load_persisted_state_from, save_persisted_state_to, CollectionStats,
+    CoreError, CsvImportOptions, EntryId, EntryState, ImportMode, ImportOptions, ImportStrategy,
+    ItemView, MemoryPakApp, OrphanRepair, QueryInput, SetItemNotesInput, SetItemStatusInput,
+};
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Debug, Parser)]
+#[command(name = "memory-pak-cli", version, about)]
+struct Cli {
+    /// State file to use instead of the desktop app's `state.json`.
+    #[arg(long, global = true, value_name = "PATH")]
+    state: Option<PathBuf>,
+    /// Print results as JSON, in the same shapes the app's backends return.
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Search the catalog, using the app's search syntax.
+    Search {
+        query: String,
+        #[arg(long, value_enum, default_value_t = Tab::Games)]
+        tab: Tab,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Show one entry and its saved state.
+    Show { id: EntryId },
+    /// Mark an entry as owned.
+    Own { id: EntryId },
+    /// Mark an entry as not owned, removing every copy.
+    Unown { id: EntryId },
+    /// Mark an entry as a favorite.
+    Fav {
+        id: EntryId,
+        /// Clear the flag instead.
+        #[arg(long)]
+        off: bool,
+    },
+    /// Put an entry on the wishlist.
+    Wish {
+        id: EntryId,
+        /// Clear the flag instead.
+        #[arg(long)]
+        off: bool,
+    },
+    /// Replace an entry's notes. An empty string clears them.
+    Note { id: EntryId, text: String },
+    /// Print collection totals.
+    Stats,
+    /// Import a JSON export, or a CSV when the file ends in `.csv`.
+    Import {
+        file: PathBuf,
+        /// JSON only. Defaults to `merge`.
+        #[arg(long, value_enum)]
+        mode: Option<Mode>,
+        /// JSON only. Defaults to `overwrite`.
+        #[arg(long, value_enum)]
+        strategy: Option<Strategy>,
+    },
+    /// Export the collection as JSON, or CSV with `--csv`.
+    Export {
+        #[arg(long)]
+        csv: bool,
+        /// Write to a file instead of stdout.
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// List saved ids the catalog no longer has, or repair them.
+    Orphans {
+        /// Move an orphan's state onto a catalog entry.
+        #[arg(long, value_name = "ID=TO", value_parser = parse_remap)]
+        remap: Vec<(EntryId, EntryId)>,
+        /// Drop an orphan's state and list memberships.
+        #[arg(long, value_name = "ID")]
+        discard: Vec<EntryId>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Tab {
+    Games,
+    Consoles,
+    Collectibles,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Mode {
+    Merge,
+    Replace,
+}
+
+impl From<Mode> for ImportMode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Merge => ImportMode::Merge,
+            Mode::Replace => ImportMode::Replace,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Strategy {
+    Overwrite,
+    KeepLocal,
+    MergeFlags,
+    ConcatNotes,
+}
+
+impl From<Strategy> for ImportStrategy {
+    fn from(strategy: Strategy) -> Self {
+        match strategy {
+            Strategy::Overwrite => ImportStrategy::Overwrite,
+            Strategy::KeepLocal => ImportStrategy::KeepLocal,
+            Strategy::MergeFlags => ImportStrategy::MergeFlags,
+            Strategy::ConcatNotes => ImportStrategy::ConcatNotes,
+        }
+    }
+}
+
+fn parse_remap(value: &str) -> Result<(EntryId, EntryId), String> {
+    let (id, to) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected ID=TO, got {value:?}"))?;
+    Ok((EntryId::from_raw(id), EntryId::from_raw(to)))
+}
+
+#[derive(Debug, Error)]
+enum CliError {
+    #[error("no data directory on this platform; pass --state")]
+    NoStatePath,
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error(transparent)]
+    Output(#[from] io::Error),
+    #[error(transparent)]
+    Core(#[from] CoreError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("--mode and --strategy only apply to JSON imports")]
+    JsonOnlyFlags,
+}
+
+fn io_error(path: &Path) -> impl FnOnce(io::Error) -> CliError + '_ {
+    move |source| CliError::Io {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli, &mut io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("memory-pak-cli: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli, out: &mut impl Write) -> Result<(), CliError> {
+    let path = match cli.state {
+        Some(path) => path,
+        None => default_state_path()
+            .map_err(CliError::Output)?
+            .ok_or(CliError::NoStatePath)?,
+    };
+    let state = load_persisted_state_from(&path).map_err(io_error(&path))?;
+    let mut app = MemoryPakApp::from_persisted_state(state);
+    let printer = Printer {
+        json: cli.json,
+        out,
+    };
+
+    match cli.command {
+        Command::Search { query, tab, limit } => {
+            let input = QueryInput {
+                search: Some(query),
+                limit: Some(limit),
+                ..QueryInput::default()
+            };
+            match tab {
+                Tab::Games => {
+                    let result = app.query_games(input);
+                    printer.print(&result, |out| {
+                        for game in &result.items {
+                            writeln!(
+                                out,
+                                "{}  {} ({}, {}){}",
+                                game.id,
+                                game.title,
+                                game.console_name,
+                                game.year,
+                                flags(&game.state)
+                            )?;
+                        }
+                        footer(out, result.items.len(), result.total, &result.error)
+                    })
+                }
+                Tab::Consoles => {
+                    let result = app.query_consoles(input);
+                    printer.print(&result, |out| {
+                        for console in &result.items {
+                            writeln!(
+                                out,
+                                "{}  {} ({}){}",
+                                console.id,
+                                console.name,
+                                console.manufacturer,
+                                flags(&console.state)
+                            )?;
+                        }
+                        footer(out, result.items.len(), result.total, &result.error)
+                    })
+                }
+                Tab::Collectibles => {
+                    let result = app.query_collectibles(input);
+                    printer.print(&result, |out| {
+                        for item in &result.items {
+                            writeln!(
+                                out,
+                                "{}  {} ({}){}",
+                                item.id,
+                                item.name,
+                                item.collection_name,
+                                flags(&item.state)
+                            )?;
+                        }
+                        footer(out, result.items.len(), result.total, &result.error)
+                    })
+                }
+            }
+        }
+        Command::Show { id } => {
+            let item = app.get_item(&id)?;
+            printer.print(&item, |out| show(out, &item))
+        }
+        Command::Own { id } => set_status(&mut app, &path, printer, id, |s| s.owned = Some(true)),
+        Command::Unown { id } => {
+            set_status(&mut app, &path, printer, id, |s| s.owned = Some(false))
+        }
+        Command::Fav { id, off } => {
+            set_status(&mut app, &path, printer, id, |s| s.favorite = Some(!off))
+        }
+        Command::Wish { id, off } => {
+            set_status(&mut app, &path, printer, id, |s| s.wishlist = Some(!off))
+        }
+        Command::Note { id, text } => {
+            let result = app.set_item_notes(SetItemNotesInput { id, notes: text })?;
+            save(&app, &path)?;
+            printer.print(&result, |out| {
+                writeln!(out, "{}{}", result.id, flags(&result.state))
+            })
+        }
+        Command::Stats => {
+            let stats = app.collection_stats();
+            printer.print(&stats, |out| print_stats(out, &stats))
+        }
+        Command::Import {
+            file,
+            mode,
+            strategy,
+        } => {
+            let is_csv = file
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+            if is_csv && (mode.is_some() || strategy.is_some()) {
+                return Err(CliError::JsonOnlyFlags);
+            }
+            let text = fs::read_to_string(&file).map_err(io_error(&file))?;
+            if is_csv {
+                let report = app.import_csv(&text, CsvImportOptions::default())?;
+                save(&app, &path)?;
+                printer.print(&report, |out| {
+                    writeln!(out, "Matched {} rows.", report.matched)?;
+                    for row in &report.unmatched {
+                        writeln!(out, "  line {}: {} ({})", row.line, row.key, row.reason)?;
+                    }
+                    Ok(())
+                })
+            } else {
+                let options = ImportOptions {
+                    mode: mode.unwrap_or(Mode::Merge).into(),
+                    strategy: strategy.unwrap_or(Strategy::Overwrite).into(),
+                };
+                let summary = app.import_json(&text, options)?;
+                save(&app, &path)?;
+                printer.print(&summary, |out| {
+                    writeln!(
+                        out,
+                        "Added {}, updated {}, removed {}.",
+                        summary.added, summary.updated, summary.removed
+                    )?;
+                    for rewrite in &summary.renamed {
+                        writeln!(out, "  renamed {} -> {}", rewrite.from, rewrite.to)?;
+                    }
+                    for id in &summary.unresolved {
+                        writeln!(out, "  unknown id {id}")?;
+                    }
+                    Ok(())
+                })
+            }
+        }
+        Command::Export { csv, output } => {
+            let data = if csv {
+                app.export_csv()
+            } else {
+                app.export_json()?
+            };
+            match output {
+                Some(file) => fs::write(&file, data).map_err(io_error(&file)),
+                None => {
+                    printer.out.write_all(data.as_bytes())?;
+                    Ok(())
+                }
+            }
+        }
+        Command::Orphans { remap, discard } => {
+            if remap.is_empty() && discard.is_empty() {
+                let orphans = app.orphans();
+                return printer.print(&orphans, |out| {
+                    for orphan in &orphans {
+                        writeln!(out, "{}{}", orphan.id, flags(&orphan.state))?;
+                        if !orphan.lists.is_empty() {
+                            writeln!(out, "  in lists: {}", orphan.lists.join(", "))?;
+                        }
+                        for suggestion in &orphan.suggestions {
+                            writeln!(
+                                out,
+                                "  maybe {}  {} ({}%)",
+                                suggestion.id, suggestion.title, suggestion.score
+                            )?;
+                        }
+                    }
+                    writeln!(out, "{} orphaned ids.", orphans.len())
+                });
+            }
+            let repairs = remap
+                .into_iter()
+                .map(|(id, to)| OrphanRepair::Remap { id, to })
+                .chain(discard.into_iter().map(|id| OrphanRepair::Discard { id }))
+                .collect();
+            let summary = app.repair_orphans(repairs)?;
+            save(&app, &path)?;
+            printer.print(&summary, |out| {
+                writeln!(
+                    out,
+                    "Remapped {}, discarded {}.",
+                    summary.remapped, summary.discarded
+                )
+            })
+        }
+    }
+}
+
+struct Printer<'a, W> {
+    json: bool,
+    out: &'a mut W,
+}
+
+impl<W: Write> Printer<'_, W> {
+    /// Writes `value` as pretty JSON in `--json` mode, or runs `text`.
+    fn print<T: Serialize>(
+        self,
+        value: &T,
+        text: impl FnOnce(&mut W) -> io::Result<()>,
+    ) -> Result<(), CliError> {
+        if self.json {
+            serde_json::to_writer_pretty(&mut *self.out, value)?;
+            writeln!(self.out)?;
+        } else {
+            text(self.out)?;
+        }
+        Ok(())
+    }
+}
+
+fn set_status<W: Write>(
+    app: &mut MemoryPakApp,
+    path: &Path,
+    printer: Printer<'_, W>,
+    id: EntryId,
+    update: impl FnOnce(&mut SetItemStatusInput),
+) -> Result<(), CliError> {
+    let mut input = SetItemStatusInput {
+        id,
+        owned: None,
+        favorite: None,
+        wishlist: None,
+    };
+    update(&mut input);
+    let result = app.set_item_status(input)?;
+    save(app, path)?;
+    printer.print(&result, |out| {
+        writeln!(out, "{}{}", result.id, flags(&result.state))
+    })
+}
+
+fn save(app: &MemoryPakApp, path: &Path) -> Result<(), CliError> {
+    save_persisted_state_to(path, app.persisted_state()).map_err(io_error(path))
+}
+
+/// `  [owned x2, favorite]`, or nothing for an untracked entry.
+fn flags(state: &EntryState) -> String {
+    let mut flags = Vec::new();
+    match state.copies.len() {
+        0 => {}
+        1 => flags.push("owned".to_string()),
+        n => flags.push(format!("owned x{n}")),
+    }
+    if state.favorite {
+        flags.push("favorite".to_string());
+    }
+    if state.wishlist {
+        flags.push("wishlist".to_string());
+    }
+    if flags.is_empty() {
+        String::new()
+    } else {
+        format!("  [{}]", flags.join(", "))
+    }
+}
+
+fn footer(
+    out: &mut impl Write,
+    shown: usize,
+    total: usize,
+    error: &Option<impl std::fmt::Display>,
+) -> io::Result<()> {
+    match error {
+        Some(error) => writeln!(out, "Invalid search: {error}"),
+        None => writeln!(out, "{shown} of {total} matches."),
+    }
+}
+
+fn show(out: &mut impl Write, item: &ItemView) -> io::Result<()> {
+    let state = match item {
+        ItemView::Console(console) => {
+            writeln!(out, "{}", console.name)?;
+            writeln!(out, "id:           {}", console.id)?;
+            writeln!(out, "manufacturer: {}", console.manufacturer)?;
+            writeln!(out, "generation:   {}", console.generation)?;
+            &console.state
+        }
+        ItemView::Game(game) => {
+            writeln!(out, "{}", game.title)?;
+            writeln!(out, "id:           {}", game.id)?;
+            writeln!(out, "console:      {}", game.console_name)?;
+            writeln!(out, "year:         {}", game.year)?;
+            writeln!(out, "developer:    {}", game.developer)?;
+            writeln!(out, "publisher:    {}", game.publisher)?;
+            &game.state
+        }
+        ItemView::Collectible(item) => {
+            writeln!(out, "{}", item.name)?;
+            writeln!(out, "id:           {}", item.id)?;
+            writeln!(out, "collection:   {}", item.collection_name)?;
+            writeln!(out, "category:     {}", item.category)?;
+            &item.state
+        }
+    };
+    writeln!(out, "copies:       {}", state.copies.len())?;
+    writeln!(out, "favorite:     {}", state.favorite)?;
+    writeln!(out, "wishlist:     {}", state.wishlist)?;
+    if !state.tags.is_empty() {
+        writeln!(out, "tags:         {}", state.tags.join(", "))?;
+    }
+    if !state.notes.is_empty() {
+        writeln!(out, "notes:        {}", state.notes)?;
+    }
+    Ok(())
+}
+
+fn print_stats(out: &mut impl Write, stats: &CollectionStats) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:<12} {:>7} {:>7} {:>7} {:>9} {:>9}",
+        "", "total", "owned", "copies", "favorite", "wishlist"
+    )?;
+    let rows = [
+        (
+            "Consoles",
+            stats.total_consoles,
+            stats.owned_consoles,
+            stats.owned_console_copies,
+            stats.favorite_consoles,
+            stats.wishlist_consoles,
+        ),
+        (
+            "Games",
+            stats.total_games,
+            stats.owned_games,
+            stats.owned_game_copies,
+            stats.favorite_games,
+            stats.wishlist_games,
+        ),
+        (
+            "Collectibles",
+            stats.total_collectibles,
+            stats.owned_collectibles,
+            stats.owned_collectible_copies,
+            stats.favorite_collectibles,
+            stats.wishlist_collectibles,
+        ),
+    ];
+    for (label, total, owned, copies, favorite, wishlist) in rows {
+        writeln!(
+            out,
+            "{label:<12} {total:>7} {owned:>7} {copies:>7} {favorite:>9} {wishlist:>9}"
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn run_with(state: &Path, args: &[&str]) -> String {
+        let mut argv = vec!["memory-pak-cli", "--state", state.to_str().unwrap()];
+        argv.extend_from_slice(args);
+        let mut out = Vec::new();
+        run(Cli::try_parse_from(argv).unwrap(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn mutations_are_saved_to_the_state_file() {
+        let dir = std::env::temp_dir().join(format!("memory-pak-cli-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let state = dir.join("state.json");
+        let mario = "game:nes/super-mario-bros";
+
+        assert!(run_with(&state, &["own", mario]).contains("[owned]"));
+        run_with(&state, &["fav", mario]);
+        run_with(&state, &["note", mario, "boxed"]);
+
+        let saved = load_persisted_state_from(&state).unwrap();
+        let entry = &saved.entries[&EntryId::from_raw(mario)];
+        assert!(entry.owned && entry.favorite);
+        assert_eq!(entry.notes, "boxed");
+
+        let stats: CollectionStats =
+            serde_json::from_str(&run_with(&state, &["--json", "stats"])).unwrap();
+        assert_eq!(stats.owned_games, 1);
+        assert!(run_with(&state, &["show", mario]).contains("notes:        boxed"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn csv_imports_refuse_json_only_flags() {
+        let dir = std::env::temp_dir().join(format!("memory-pak-cli-csv-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let state = dir.join("state.json");
+        let csv = dir.join("list.csv");
+        fs::write(&csv, "id,owned\ngame:nes/super-mario-bros,true\n").unwrap();
+
+        let cli = Cli::try_parse_from([
+            "memory-pak-cli",
+            "--state",
+            state.to_str().unwrap(),
+            "import",
+            csv.to_str().unwrap(),
+            "--mode",
+            "replace",
+        ])
+        .unwrap();
+        assert!(matches!(
+            run(cli, &mut Vec::new()),
+            Err(CliError::JsonOnlyFlags)
+        ));
+        assert!(!state.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,43 @@
+//! Times `query_games` for a few typical searches.
+//!
+//! Run with `cargo bench -p memory_pak_core --bench query_games`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use memory_pak_core::{MemoryPakApp, QueryInput};
+
+const SEARCHES: &[&str] = &[
+    "mario",
+    "castelvania",
+    "zelda ocarnia",
+    "publisher:capcom year:1990..1995",
+    "\"street fighter\"",
+    "a",
+];
+
+fn main() {
+    let app = MemoryPakApp::default();
+    // Build the catalog before timing anything.
+    black_box(app.query_games(QueryInput::default()));
+
+    for search in SEARCHES {
+        let query = || {
+            app.query_games(QueryInput {
+                search: Some(search.to_string()),
+                limit: Some(100),
+                ..Default::default()
+            })
+        };
+        let total = query().total;
+
+        let mut runs = 0u32;
+        let started = Instant::now();
+        while runs < 20 || started.elapsed() < Duration::from_secs(2) {
+            black_box(query());
+            runs += 1;
+        }
+        let per_query = started.elapsed() / runs;
+        println!("{search:<36} {total:>6} hits  {per_query:>12.2?}/query");
+    }
+}
@@ -1,26 +1,42 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::ffi::OsStr;
+use std::fmt::Display;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 
 #[path = "src/compiled.rs"]
 mod compiled;
+#[path = "src/normalize.rs"]
+mod normalize;
 
 use compiled::{
-    CompiledCatalog, CompiledCollectible, CompiledCollection, CompiledConsole, CompiledGame,
+    CompiledAlias, CompiledCatalog, CompiledCollectible, CompiledCollection, CompiledConsole,
+    CompiledGame, CompiledPosting, CompiledReleases, CompiledSearchIndex,
 };
+use normalize::normalize_for_search;
 
 /// Per-console game list under `database/games/*.json`.
 #[derive(Debug, Deserialize)]
 struct GameFile {
+    #[serde(default)]
+    schema_version: Option<u32>,
+    #[serde(default)]
+    counts: Option<FileCounts>,
     console: GameFileConsoleHeader,
     #[serde(default)]
     games: Vec<RawGame>,
 }
 
+/// The `counts` block of a game or collectible file.
+#[derive(Debug, Deserialize)]
+struct FileCounts {
+    total: usize,
+}
+
 #[derive(Debug, Deserialize)]
 struct GameFileConsoleHeader {
     id: String,
@@ -29,6 +45,8 @@ struct GameFileConsoleHeader {
 /// `database/consoles.json` — canonical console metadata.
 #[derive(Debug, Deserialize)]
 struct ConsolesFile {
+    #[serde(default)]
+    schema_version: Option<u32>,
     consoles: Vec<ConsoleListEntry>,
 }
 
@@ -47,6 +65,22 @@ struct ConsoleListEntry {
     abbreviation: String,
 }
 
+/// `database/aliases.json` — ids retired by slug renames or reordered
+/// duplicates, mapped to the id that replaced them.
+#[derive(Debug, Deserialize)]
+struct AliasesFile {
+    #[serde(default)]
+    schema_version: Option<u32>,
+    #[serde(default)]
+    aliases: Vec<RawAlias>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawAlias {
+    from: String,
+    to: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct RawGame {
     title: String,
@@ -57,10 +91,32 @@ struct RawGame {
     publisher: Option<String>,
     #[serde(default)]
     first_release: Option<String>,
+    #[serde(default)]
+    releases: RawReleases,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawReleases {
+    #[serde(default)]
+    jp: Option<String>,
+    #[serde(default)]
+    na: Option<String>,
+    #[serde(default)]
+    pal: Option<String>,
+    #[serde(default)]
+    eu: Option<String>,
+    #[serde(default)]
+    au: Option<String>,
+    #[serde(default)]
+    br: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct CollectibleFile {
+    #[serde(default)]
+    schema_version: Option<u32>,
+    #[serde(default)]
+    counts: Option<FileCounts>,
     collection: CollectionHeader,
     #[serde(default)]
     items: Vec<RawCollectible>,
@@ -96,109 +152,302 @@ struct RawCollectible {
     year: Option<u8>,
 }
 
+/// Schema version every database file must declare.
+const SCHEMA_VERSION: u32 = 1;
+
+/// Set to `1` (as CI does) to fail the build on warnings too.
+const STRICT_ENV: &str = "MEMORY_PAK_STRICT_CATALOG";
+
 fn main() {
     let manifest_dir =
         PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR"));
-    let db_dir = manifest_dir.join("..").join("..").join("database");
+    let repo_dir = manifest_dir.join("..").join("..");
+    let db_dir = repo_dir.join("database");
     let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR"));
 
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=src/compiled.rs");
-
-    let (consoles, games) = load_consoles_and_games(&db_dir, &db_dir.join("games"));
-    let (collections, collectibles) = load_collectibles(&db_dir.join("collectibles"));
+    println!("cargo:rerun-if-changed=src/normalize.rs");
+    println!("cargo:rerun-if-env-changed={STRICT_ENV}");
+
+    let mut problems = Problems::new(repo_dir);
+    let (consoles, games) = load_consoles_and_games(&db_dir, &db_dir.join("games"), &mut problems);
+    let (collections, collectibles) =
+        load_collectibles(&db_dir.join("collectibles"), &mut problems);
+    let aliases = load_aliases(
+        &db_dir.join("aliases.json"),
+        &consoles,
+        &games,
+        &collectibles,
+        &mut problems,
+    );
+    problems.finish(env::var(STRICT_ENV).is_ok_and(|v| v == "1"));
+
+    let search = build_search_index(&games);
 
     let catalog = CompiledCatalog {
         consoles,
         games,
         collections,
         collectibles,
+        search,
+        aliases,
     };
 
     let bytes = postcard::to_allocvec(&catalog).expect("serialize compiled catalog");
     fs::write(out_dir.join("catalog.postcard"), &bytes).expect("write catalog.postcard");
 }
 
+/// Everything wrong with the database, collected so one build reports it
+/// all. Errors always fail the build; warnings only in strict mode.
+/// Notices never do: they are gaps the source lists often have, such as a
+/// game without a developer, and are reported once per file.
+struct Problems {
+    /// Paths are reported relative to this.
+    root: PathBuf,
+    errors: Vec<String>,
+    warnings: Vec<String>,
+    notices: Vec<String>,
+}
+
+impl Problems {
+    fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            notices: Vec::new(),
+        }
+    }
+
+    fn error(&mut self, path: &Path, message: impl Display) {
+        let line = self.line(path, message);
+        self.errors.push(line);
+    }
+
+    fn warning(&mut self, path: &Path, message: impl Display) {
+        let line = self.line(path, message);
+        self.warnings.push(line);
+    }
+
+    fn notice(&mut self, path: &Path, message: impl Display) {
+        let line = self.line(path, message);
+        self.notices.push(line);
+    }
+
+    fn line(&self, path: &Path, message: impl Display) -> String {
+        let path = path.strip_prefix(&self.root).unwrap_or(path);
+        format!("{}: {message}", path.display())
+    }
+
+    fn finish(self, strict: bool) {
+        if self.errors.is_empty() && (!strict || self.warnings.is_empty()) {
+            for warning in &self.warnings {
+                println!("cargo:warning={warning}");
+            }
+            for notice in &self.notices {
+                println!("cargo:warning=notice: {notice}");
+            }
+            return;
+        }
+
+        let mut report = format!(
+            "catalog validation failed with {} error(s) and {} warning(s){}:\n",
+            self.errors.len(),
+            self.warnings.len(),
+            if strict { " (strict)" } else { "" }
+        );
+        for error in &self.errors {
+            report.push_str(&format!("  error: {error}\n"));
+        }
+        for warning in &self.warnings {
+            report.push_str(&format!("  warning: {warning}\n"));
+        }
+        for notice in &self.notices {
+            report.push_str(&format!("  notice: {notice}\n"));
+        }
+        panic!("{report}");
+    }
+}
+
+/// Reads and parses one database file, recording why when it cannot.
+fn read_json<T: DeserializeOwned>(path: &Path, problems: &mut Problems) -> Option<T> {
+    println!("cargo:rerun-if-changed={}", path.display());
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            problems.error(path, format!("cannot read: {err}"));
+            return None;
+        }
+    };
+    match serde_json::from_str(&text) {
+        Ok(parsed) => Some(parsed),
+        Err(err) => {
+            problems.error(path, format!("cannot parse: {err}"));
+            None
+        }
+    }
+}
+
+fn check_schema(path: &Path, version: Option<u32>, problems: &mut Problems) {
+    match version {
+        Some(SCHEMA_VERSION) => {}
+        Some(other) => problems.error(
+            path,
+            format!("schema_version {other}, expected {SCHEMA_VERSION}"),
+        ),
+        None => problems.error(path, "missing schema_version"),
+    }
+}
+
+fn check_count(path: &Path, counts: Option<&FileCounts>, actual: usize, problems: &mut Problems) {
+    if let Some(counts) = counts {
+        if counts.total != actual {
+            problems.warning(
+                path,
+                format!(
+                    "counts.total is {} but the file lists {actual}",
+                    counts.total
+                ),
+            );
+        }
+    }
+}
+
+/// The `*.json` files directly inside `dir`, sorted so ids are stable.
+fn json_files(dir: &Path, problems: &mut Problems) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            problems.error(dir, format!("cannot list: {err}"));
+            return Vec::new();
+        }
+    };
+    let mut file_paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().and_then(OsStr::to_str) == Some("json"))
+        .collect();
+    file_paths.sort();
+    file_paths
+}
+
+/// Appends `~N` to repeated slugs so ids stay unique. `details` tells
+/// intended variants apart (a figure re-released for another game); a repeat
+/// with the same details is a copy-paste duplicate and gets a warning, as
+/// its `~N` id depends on file order.
+fn unique_slug(
+    seen_slugs: &mut HashMap<String, Vec<String>>,
+    base: String,
+    details: String,
+    path: &Path,
+    problems: &mut Problems,
+) -> String {
+    let seen = seen_slugs.entry(base.clone()).or_default();
+    if seen.contains(&details) {
+        problems.warning(
+            path,
+            format!(
+                "duplicate slug {base:?} stored as {base}~{}",
+                seen.len() + 1
+            ),
+        );
+    }
+    seen.push(details);
+    if seen.len() == 1 {
+        base
+    } else {
+        format!("{base}~{}", seen.len())
+    }
+}
+
 fn load_consoles_and_games(
     db_dir: &Path,
     games_dir: &Path,
+    problems: &mut Problems,
 ) -> (Vec<CompiledConsole>, Vec<CompiledGame>) {
     let consoles_path = db_dir.join("consoles.json");
-    println!("cargo:rerun-if-changed={}", consoles_path.display());
-
-    let consoles_text = fs::read_to_string(&consoles_path).unwrap_or_else(|err| {
-        panic!("read {}: {err}", consoles_path.display());
-    });
-    let consoles_file: ConsolesFile = serde_json::from_str(&consoles_text).unwrap_or_else(|err| {
-        panic!("parse {}: {err}", consoles_path.display());
-    });
-
     let mut consoles: Vec<CompiledConsole> = Vec::new();
     let mut known_short_ids: HashMap<String, String> = HashMap::new();
 
-    for entry in &consoles_file.consoles {
-        let console_id = format!("console:{}", entry.id);
-        known_short_ids.insert(entry.id.clone(), console_id.clone());
-        consoles.push(CompiledConsole {
-            id: console_id,
-            short_id: entry.id.clone(),
-            name: entry.name.clone(),
-            manufacturer: entry.manufacturer.clone(),
-            family: entry.family.clone(),
-            form_factor: entry.form_factor.clone(),
-            generation: entry.generation.unwrap_or(0),
-            abbreviation: entry.abbreviation.clone(),
-        });
-    }
-
-    let mut games: Vec<CompiledGame> = Vec::new();
-    let mut seen_slugs: HashMap<String, usize> = HashMap::new();
-
-    let mut file_paths: Vec<PathBuf> = Vec::new();
-    if games_dir.is_dir() {
-        for entry in fs::read_dir(games_dir).unwrap_or_else(|err| {
-            panic!("read {}: {err}", games_dir.display());
-        }) {
-            let entry = entry.expect("read games dir entry");
-            let path = entry.path();
-            if !path.is_file() {
-                continue;
+    if let Some(consoles_file) = read_json::<ConsolesFile>(&consoles_path, problems) {
+        check_schema(&consoles_path, consoles_file.schema_version, problems);
+        for entry in consoles_file.consoles {
+            let console_id = format!("console:{}", entry.id);
+            if entry.id.trim().is_empty() || entry.name.trim().is_empty() {
+                problems.error(
+                    &consoles_path,
+                    format!("console {console_id:?} needs an id and a name"),
+                );
             }
-            if path.extension().and_then(OsStr::to_str) != Some("json") {
+            if known_short_ids
+                .insert(entry.id.clone(), console_id.clone())
+                .is_some()
+            {
+                problems.error(
+                    &consoles_path,
+                    format!("duplicate console id {:?}", entry.id),
+                );
                 continue;
             }
-            file_paths.push(path);
+            consoles.push(CompiledConsole {
+                id: console_id,
+                short_id: entry.id,
+                name: entry.name,
+                manufacturer: entry.manufacturer,
+                family: entry.family,
+                form_factor: entry.form_factor,
+                generation: entry.generation.unwrap_or(0),
+                abbreviation: entry.abbreviation,
+            });
         }
     }
-    file_paths.sort();
 
+    let mut games: Vec<CompiledGame> = Vec::new();
+    let mut seen_slugs: HashMap<String, Vec<String>> = HashMap::new();
+
+    let file_paths = if games_dir.is_dir() {
+        json_files(games_dir, problems)
+    } else {
+        Vec::new()
+    };
     for path in file_paths {
-        println!("cargo:rerun-if-changed={}", path.display());
-        let text = fs::read_to_string(&path).unwrap_or_else(|err| {
-            panic!("read {}: {err}", path.display());
-        });
-        let parsed: GameFile = serde_json::from_str(&text).unwrap_or_else(|err| {
-            panic!("parse {}: {err}", path.display());
-        });
+        let Some(parsed) = read_json::<GameFile>(&path, problems) else {
+            continue;
+        };
+        check_schema(&path, parsed.schema_version, problems);
+        check_count(&path, parsed.counts.as_ref(), parsed.games.len(), problems);
 
         let short_id = parsed.console.id.as_str();
-        let console_id = known_short_ids.get(short_id).unwrap_or_else(|| {
-            panic!(
-                "{} references console id {:?} which is missing from database/consoles.json",
-                path.display(),
-                short_id
-            )
-        });
+        let Some(console_id) = known_short_ids.get(short_id) else {
+            problems.error(
+                &path,
+                format!("console id {short_id:?} is missing from database/consoles.json"),
+            );
+            continue;
+        };
 
-        for raw in parsed.games {
-            let base = format!("{short_id}/{}", raw.slug);
-            let count = seen_slugs.entry(base.clone()).or_insert(0);
-            let final_slug = if *count == 0 {
-                base.clone()
-            } else {
-                format!("{base}~{}", *count + 1)
-            };
-            *count += 1;
+        let mut without_developer = Vec::new();
+        let mut without_year = Vec::new();
+        for (index, raw) in parsed.games.into_iter().enumerate() {
+            if raw.title.trim().is_empty() || raw.slug.trim().is_empty() {
+                problems.error(&path, format!("game #{index} needs a title and a slug"));
+                continue;
+            }
+            check_dates(&path, &raw, problems);
+            let year = parse_year(raw.first_release.as_deref());
+            if raw.developer.as_deref().is_none_or(|d| d.trim().is_empty()) {
+                without_developer.push(raw.slug.clone());
+            }
+            if year == 0 {
+                without_year.push(raw.slug.clone());
+            }
+            let final_slug = unique_slug(
+                &mut seen_slugs,
+                format!("{short_id}/{}", raw.slug),
+                String::new(),
+                &path,
+                problems,
+            );
 
             games.push(CompiledGame {
                 id: format!("game:{final_slug}"),
@@ -207,15 +456,67 @@ fn load_consoles_and_games(
                 title: raw.title,
                 developer: raw.developer.unwrap_or_default(),
                 publisher: raw.publisher.unwrap_or_default(),
-                year: parse_year(raw.first_release.as_deref()),
+                year,
+                releases: compile_releases(raw.releases),
             });
         }
+        notice_gaps(&path, "a developer", &without_developer, problems);
+        notice_gaps(
+            &path,
+            "a release year (compiled as 0)",
+            &without_year,
+            problems,
+        );
     }
 
     (consoles, games)
 }
 
-fn load_collectibles(dir: &Path) -> (Vec<CompiledCollection>, Vec<CompiledCollectible>) {
+/// Notes how many of a file's games lack `what`, naming the first few.
+fn notice_gaps(path: &Path, what: &str, slugs: &[String], problems: &mut Problems) {
+    const EXAMPLES: usize = 3;
+    if slugs.is_empty() {
+        return;
+    }
+    let mut examples = slugs[..slugs.len().min(EXAMPLES)].join(", ");
+    if slugs.len() > EXAMPLES {
+        examples.push_str(", ...");
+    }
+    problems.notice(
+        path,
+        format!("{} game(s) without {what}: {examples}", slugs.len()),
+    );
+}
+
+/// Warns about dates `release_date` would drop. Missing dates are only
+/// noticed, once per file, by `load_consoles_and_games`.
+fn check_dates(path: &Path, raw: &RawGame, problems: &mut Problems) {
+    let releases = &raw.releases;
+    let dates = [
+        ("first_release", &raw.first_release),
+        ("jp", &releases.jp),
+        ("na", &releases.na),
+        ("pal", &releases.pal),
+        ("eu", &releases.eu),
+        ("au", &releases.au),
+        ("br", &releases.br),
+    ];
+    for (field, date) in dates {
+        if let Some(date) = date {
+            if release_date(Some(date.clone())).is_none() {
+                problems.warning(
+                    path,
+                    format!("{:?}: unparsable {field} date {date:?}", raw.slug),
+                );
+            }
+        }
+    }
+}
+
+fn load_collectibles(
+    dir: &Path,
+    problems: &mut Problems,
+) -> (Vec<CompiledCollection>, Vec<CompiledCollectible>) {
     let mut collections: Vec<CompiledCollection> = Vec::new();
     let mut collectibles: Vec<CompiledCollectible> = Vec::new();
 
@@ -223,30 +524,14 @@ fn load_collectibles(dir: &Path) -> (Vec<CompiledCollection>, Vec<CompiledCollec
         return (collections, collectibles);
     }
 
-    let mut file_paths: Vec<PathBuf> = Vec::new();
-    for entry in fs::read_dir(dir).expect("read collectibles directory") {
-        let entry = entry.expect("read collectibles entry");
-        let path = entry.path();
-        if !path.is_file() {
-            continue;
-        }
-        if path.extension().and_then(OsStr::to_str) != Some("json") {
-            continue;
-        }
-        file_paths.push(path);
-    }
-    file_paths.sort();
+    let mut seen_slugs: HashMap<String, Vec<String>> = HashMap::new();
 
-    let mut seen_slugs: HashMap<String, usize> = HashMap::new();
-
-    for path in file_paths {
-        println!("cargo:rerun-if-changed={}", path.display());
-        let text = fs::read_to_string(&path).unwrap_or_else(|err| {
-            panic!("read {}: {err}", path.display());
-        });
-        let parsed: CollectibleFile = serde_json::from_str(&text).unwrap_or_else(|err| {
-            panic!("parse {}: {err}", path.display());
-        });
+    for path in json_files(dir, problems) {
+        let Some(parsed) = read_json::<CollectibleFile>(&path, problems) else {
+            continue;
+        };
+        check_schema(&path, parsed.schema_version, problems);
+        check_count(&path, parsed.counts.as_ref(), parsed.items.len(), problems);
 
         collections.push(CompiledCollection {
             id: parsed.collection.id.clone(),
@@ -255,15 +540,27 @@ fn load_collectibles(dir: &Path) -> (Vec<CompiledCollection>, Vec<CompiledCollec
             kind: parsed.collection.kind,
         });
 
-        for raw in parsed.items {
-            let base = format!("{}/{}", parsed.collection.id, raw.slug);
-            let count = seen_slugs.entry(base.clone()).or_insert(0);
-            let final_slug = if *count == 0 {
-                base.clone()
-            } else {
-                format!("{base}~{}", *count + 1)
-            };
-            *count += 1;
+        for (index, raw) in parsed.items.into_iter().enumerate() {
+            if raw.name.trim().is_empty() || raw.slug.trim().is_empty() {
+                problems.error(&path, format!("item #{index} needs a name and a slug"));
+                continue;
+            }
+            let final_slug = unique_slug(
+                &mut seen_slugs,
+                format!("{}/{}", parsed.collection.id, raw.slug),
+                format!(
+                    "{:?}",
+                    (
+                        &raw.category,
+                        &raw.pack,
+                        &raw.game,
+                        &raw.base_color,
+                        raw.year
+                    )
+                ),
+                &path,
+                problems,
+            );
 
             collectibles.push(CompiledCollectible {
                 id: format!("collectible:{final_slug}"),
@@ -280,6 +577,113 @@ fn load_collectibles(dir: &Path) -> (Vec<CompiledCollection>, Vec<CompiledCollec
     (collections, collectibles)
 }
 
+/// Reads the alias table and flattens chains (`a -> b -> c` becomes
+/// `a -> c`). Every alias must end at a live id, and no live id may itself
+/// be aliased.
+fn load_aliases(
+    path: &Path,
+    consoles: &[CompiledConsole],
+    games: &[CompiledGame],
+    collectibles: &[CompiledCollectible],
+    problems: &mut Problems,
+) -> Vec<CompiledAlias> {
+    if !path.exists() {
+        println!("cargo:rerun-if-changed={}", path.display());
+        return Vec::new();
+    }
+    let Some(parsed) = read_json::<AliasesFile>(path, problems) else {
+        return Vec::new();
+    };
+    check_schema(path, parsed.schema_version, problems);
+
+    let live: HashSet<&str> = consoles
+        .iter()
+        .map(|c| c.id.as_str())
+        .chain(games.iter().map(|g| g.id.as_str()))
+        .chain(collectibles.iter().map(|c| c.id.as_str()))
+        .collect();
+    let mut targets: BTreeMap<&str, &str> = BTreeMap::new();
+    for alias in &parsed.aliases {
+        if live.contains(alias.from.as_str()) {
+            problems.error(
+                path,
+                format!("{:?} is aliased but is still a catalog id", alias.from),
+            );
+        } else if alias.from.split(':').next() != alias.to.split(':').next() {
+            problems.error(
+                path,
+                format!(
+                    "{:?} and {:?} are different kinds of entry",
+                    alias.from, alias.to
+                ),
+            );
+        } else if targets.insert(&alias.from, &alias.to).is_some() {
+            problems.error(path, format!("{:?} is aliased twice", alias.from));
+        }
+    }
+
+    let mut aliases = Vec::with_capacity(targets.len());
+    for &from in targets.keys() {
+        let mut to = targets[from];
+        let mut hops = 0;
+        while let Some(&next) = targets.get(to) {
+            hops += 1;
+            if hops > targets.len() {
+                break;
+            }
+            to = next;
+        }
+        if hops > targets.len() {
+            problems.error(path, format!("alias cycle through {from:?}"));
+        } else if !live.contains(to) {
+            problems.error(
+                path,
+                format!("{from:?} resolves to {to:?}, which is not a catalog id"),
+            );
+        } else {
+            aliases.push(CompiledAlias {
+                from: from.to_string(),
+                to: to.to_string(),
+            });
+        }
+    }
+    aliases
+}
+
+/// Maps every normalized word of a game's title, publisher and developer to
+/// the positions of the games that contain it.
+fn build_search_index(games: &[CompiledGame]) -> CompiledSearchIndex {
+    let mut postings: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+    for (position, game) in games.iter().enumerate() {
+        let position = u32::try_from(position).expect("game count fits in u32");
+        for text in [&game.title, &game.publisher, &game.developer] {
+            for word in normalize_for_search(text).split_whitespace() {
+                let games = postings.entry(word.to_string()).or_default();
+                if games.last() != Some(&position) {
+                    games.push(position);
+                }
+            }
+        }
+    }
+
+    let words = postings
+        .into_iter()
+        .map(|(word, games)| {
+            let mut previous = 0;
+            let game_gaps = games
+                .into_iter()
+                .map(|position| {
+                    let gap = position - previous;
+                    previous = position;
+                    gap
+                })
+                .collect();
+            CompiledPosting { word, game_gaps }
+        })
+        .collect();
+    CompiledSearchIndex { words }
+}
+
 fn parse_year(date: Option<&str>) -> u32 {
     let Some(date) = date else { return 0 };
     date.split('-')
@@ -287,3 +691,33 @@ fn parse_year(date: Option<&str>) -> u32 {
         .and_then(|y| y.parse::<u32>().ok())
         .unwrap_or(0)
 }
+
+fn compile_releases(raw: RawReleases) -> CompiledReleases {
+    CompiledReleases {
+        jp: release_date(raw.jp),
+        na: release_date(raw.na),
+        pal: release_date(raw.pal),
+        eu: release_date(raw.eu),
+        au: release_date(raw.au),
+        br: release_date(raw.br),
+    }
+}
+
+/// Keeps only ISO `YYYY`, `YYYY-MM` or `YYYY-MM-DD` dates so they sort lexically.
+fn release_date(date: Option<String>) -> Option<String> {
+    let date = date?;
+    let mut parts = date.split('-');
+    let year = parts.next()?;
+    if year.len() != 4 || !year.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    for part in parts.by_ref().take(2) {
+        if part.len() != 2 || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(date)
+}
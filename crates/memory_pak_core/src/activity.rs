@@ -0,0 +1,255 @@
+//! Timestamped record of what changed on each entry, kept with the persisted
+//! state so "when did I add this to my wishlist" has an answer later.
+//!
+//! Events are only ever appended. The optional [`ActivityPolicy`] is what
+//! removes them, oldest first.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::ids::EntryId;
+use crate::model::{EntryState, OwnedCopy, PlayStatus};
+
+/// Events returned by a recent-activity query without a `limit`.
+pub const DEFAULT_ACTIVITY_LIMIT: usize = 50;
+
+/// One field of an entry going from `old` to `new`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "field", rename_all = "camelCase")]
+pub enum ActivityChange {
+    Owned {
+        old: bool,
+        new: bool,
+    },
+    Favorite {
+        old: bool,
+        new: bool,
+    },
+    Wishlist {
+        old: bool,
+        new: bool,
+    },
+    Notes {
+        old: String,
+        new: String,
+    },
+    Copies {
+        old: Vec<OwnedCopy>,
+        new: Vec<OwnedCopy>,
+    },
+    PlayStatus {
+        old: Option<PlayStatus>,
+        new: Option<PlayStatus>,
+    },
+    CompletedOn {
+        old: Option<NaiveDate>,
+        new: Option<NaiveDate>,
+    },
+    Tags {
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+}
+
+impl ActivityChange {
+    /// Every field that differs between `old` and `new`, in `EntryState`
+    /// field order.
+    pub fn between(old: &EntryState, new: &EntryState) -> Vec<Self> {
+        let mut changes = Vec::new();
+        macro_rules! diff {
+            ($field:ident, $variant:ident) => {
+                if old.$field != new.$field {
+                    changes.push(Self::$variant {
+                        old: old.$field.clone(),
+                        new: new.$field.clone(),
+                    });
+                }
+            };
+        }
+        diff!(owned, Owned);
+        diff!(favorite, Favorite);
+        diff!(wishlist, Wishlist);
+        diff!(notes, Notes);
+        diff!(copies, Copies);
+        diff!(play_status, PlayStatus);
+        diff!(completed_on, CompletedOn);
+        diff!(tags, Tags);
+        changes
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityEvent {
+    pub at: DateTime<Utc>,
+    pub id: EntryId,
+    #[serde(flatten)]
+    pub change: ActivityChange,
+}
+
+/// Limits applied after every change. Both unset keeps everything.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ActivityPolicy {
+    /// Newest events kept.
+    pub max_events: Option<usize>,
+    /// Events older than this are dropped.
+    pub max_age_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityLog {
+    /// Oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<ActivityEvent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<ActivityPolicy>,
+}
+
+impl ActivityLog {
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty() && self.policy.is_none()
+    }
+
+    /// Appends an event for each field that differs between `old` and `new`.
+    pub(crate) fn record(
+        &mut self,
+        id: &EntryId,
+        old: &EntryState,
+        new: &EntryState,
+        at: DateTime<Utc>,
+    ) {
+        self.events
+            .extend(
+                ActivityChange::between(old, new)
+                    .into_iter()
+                    .map(|change| ActivityEvent {
+                        at,
+                        id: id.clone(),
+                        change,
+                    }),
+            );
+    }
+
+    /// Drops what the policy no longer keeps and returns how many events
+    /// that was.
+    pub(crate) fn compact(&mut self, now: DateTime<Utc>) -> usize {
+        let Some(policy) = self.policy else {
+            return 0;
+        };
+        let before = self.events.len();
+        if let Some(days) = policy.max_age_days {
+            let cutoff = now - Duration::days(i64::from(days));
+            self.events.retain(|event| event.at >= cutoff);
+        }
+        if let Some(max) = policy.max_events {
+            let excess = self.events.len().saturating_sub(max);
+            self.events.drain(..excess);
+        }
+        before - self.events.len()
+    }
+}
+
+/// Filters for [`crate::MemoryPakApp::recent_activity`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ActivityQuery {
+    /// Most events returned; [`DEFAULT_ACTIVITY_LIMIT`] when unset.
+    pub limit: Option<usize>,
+    /// Only events at or after this time.
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// An event with the name of the entry it is about, for display.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityView {
+    pub at: DateTime<Utc>,
+    pub id: EntryId,
+    /// `None` when the catalog no longer has the entry.
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub change: ActivityChange,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc()
+    }
+
+    #[test]
+    fn only_changed_fields_are_recorded() {
+        let id = EntryId::from_raw("game:nes/super-mario-bros");
+        let old = EntryState {
+            favorite: true,
+            notes: "boxed".into(),
+            ..EntryState::default()
+        };
+        let new = EntryState {
+            favorite: true,
+            wishlist: true,
+            notes: "loose".into(),
+            ..EntryState::default()
+        };
+        let mut log = ActivityLog::default();
+        log.record(&id, &old, &new, at(1));
+        let changes: Vec<_> = log.events.iter().map(|e| e.change.clone()).collect();
+        assert_eq!(
+            changes,
+            vec![
+                ActivityChange::Wishlist {
+                    old: false,
+                    new: true
+                },
+                ActivityChange::Notes {
+                    old: "boxed".into(),
+                    new: "loose".into()
+                },
+            ]
+        );
+
+        let json = serde_json::to_string(&log.events[0]).unwrap();
+        assert!(json.contains(r#""field":"wishlist","old":false,"new":true"#));
+        assert_eq!(
+            serde_json::from_str::<ActivityEvent>(&json).unwrap(),
+            log.events[0]
+        );
+    }
+
+    #[test]
+    fn compaction_drops_old_then_excess_events() {
+        let id = EntryId::from_raw("console:nes");
+        let owned = EntryState {
+            owned: true,
+            ..EntryState::default()
+        };
+        let mut log = ActivityLog::default();
+        for day in 1..=5 {
+            log.record(&id, &EntryState::default(), &owned, at(day));
+        }
+        assert_eq!(log.compact(at(30)), 0);
+
+        log.policy = Some(ActivityPolicy {
+            max_events: None,
+            max_age_days: Some(27),
+        });
+        assert_eq!(log.compact(at(30)), 2);
+        assert_eq!(log.events[0].at, at(3));
+
+        log.policy = Some(ActivityPolicy {
+            max_events: Some(1),
+            max_age_days: Some(27),
+        });
+        assert_eq!(log.compact(at(30)), 2);
+        assert_eq!(log.events.len(), 1);
+        assert_eq!(log.events[0].at, at(5));
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ids::EntryId;
+use crate::import_export::{merge_entry, ExportData, ImportStrategy};
+use crate::model::{Catalog, PersistedState};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IdRewrite {
+    pub from: EntryId,
+    pub to: EntryId,
+}
+
+/// Ids moved to their replacement through the catalog's alias table, and
+/// ids that are neither live nor aliased. Unresolved ids are kept as they
+/// are so nothing the user recorded is lost.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IdReport {
+    pub rewritten: Vec<IdRewrite>,
+    pub unresolved: Vec<EntryId>,
+}
+
+impl IdReport {
+    pub fn is_empty(&self) -> bool {
+        self.rewritten.is_empty() && self.unresolved.is_empty()
+    }
+}
+
+#[derive(Default)]
+struct Resolver {
+    rewritten: BTreeMap<EntryId, EntryId>,
+    unresolved: BTreeSet<EntryId>,
+}
+
+impl Resolver {
+    fn resolve(&mut self, catalog: &Catalog, id: &EntryId) -> EntryId {
+        match catalog.resolve(id) {
+            Some(live) if live == id => live.clone(),
+            Some(live) => {
+                self.rewritten.insert(id.clone(), live.clone());
+                live.clone()
+            }
+            None => {
+                self.unresolved.insert(id.clone());
+                id.clone()
+            }
+        }
+    }
+
+    fn finish(self) -> IdReport {
+        IdReport {
+            rewritten: self
+                .rewritten
+                .into_iter()
+                .map(|(from, to)| IdRewrite { from, to })
+                .collect(),
+            unresolved: self.unresolved.into_iter().collect(),
+        }
+    }
+}
+
+/// Moves entries and list members saved under retired ids to their live
+/// ids. An entry tracked under both ids is merged with
+/// [`ImportStrategy::MergeFlags`], the live one taking precedence.
+pub(crate) fn rewrite_state_ids(catalog: &Catalog, state: &mut PersistedState) -> IdReport {
+    let mut resolver = Resolver::default();
+    let stale: Vec<EntryId> = state
+        .entries
+        .keys()
+        .filter(|id| resolver.resolve(catalog, id) != **id)
+        .cloned()
+        .collect();
+    for id in stale {
+        let moved = state.entries.remove(&id).unwrap_or_default();
+        let live = state
+            .entries
+            .entry(resolver.rewritten[&id].clone())
+            .or_default();
+        *live = merge_entry(live, moved, ImportStrategy::MergeFlags);
+    }
+    for list in &mut state.lists {
+        rewrite_list(catalog, &mut resolver, &mut list.entries);
+    }
+    resolver.finish()
+}
+
+/// Same as [`rewrite_state_ids`], for a file about to be imported.
+pub(crate) fn rewrite_import_ids(catalog: &Catalog, import: &mut ExportData) -> IdReport {
+    let mut resolver = Resolver::default();
+    for entry in &mut import.entries {
+        entry.id = resolver.resolve(catalog, &entry.id);
+    }
+    for list in &mut import.lists {
+        rewrite_list(catalog, &mut resolver, &mut list.entries);
+    }
+    resolver.finish()
+}
+
+fn rewrite_list(catalog: &Catalog, resolver: &mut Resolver, entries: &mut Vec<EntryId>) {
+    let mut seen = HashSet::new();
+    *entries = entries
+        .iter()
+        .map(|id| resolver.resolve(catalog, id))
+        .filter(|id| seen.insert(id.clone()))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::catalog;
+    use crate::model::{EntryList, EntryState};
+
+    fn renamed_catalog() -> (Catalog, EntryId, EntryId) {
+        let mut catalog = catalog().clone();
+        let old = EntryId::from_raw("game:nes/super-mario-bros-old");
+        let live = EntryId::from_raw("game:nes/super-mario-bros");
+        catalog.aliases.insert(old.clone(), live.clone());
+        (catalog, old, live)
+    }
+
+    #[test]
+    fn stale_state_ids_move_to_their_alias_target() {
+        let (catalog, old, live) = renamed_catalog();
+        let gone = EntryId::from_raw("game:nes/no-such-game");
+        let mut state = PersistedState::default();
+        state.entries.insert(
+            old.clone(),
+            EntryState {
+                favorite: true,
+                notes: "from the old slug".to_string(),
+                ..EntryState::default()
+            },
+        );
+        state.entries.insert(
+            live.clone(),
+            EntryState {
+                wishlist: true,
+                ..EntryState::default()
+            },
+        );
+        state.entries.insert(gone.clone(), EntryState::default());
+        state.lists.push(EntryList {
+            name: "Shelf".to_string(),
+            entries: vec![old.clone(), live.clone(), gone.clone()],
+        });
+
+        let report = rewrite_state_ids(&catalog, &mut state);
+
+        assert_eq!(
+            report.rewritten,
+            vec![IdRewrite {
+                from: old.clone(),
+                to: live.clone()
+            }]
+        );
+        assert_eq!(report.unresolved, vec![gone.clone()]);
+        assert!(!state.entries.contains_key(&old));
+        let merged = &state.entries[&live];
+        assert!(merged.favorite && merged.wishlist);
+        assert_eq!(merged.notes, "from the old slug");
+        assert!(state.entries.contains_key(&gone));
+        assert_eq!(state.lists[0].entries, vec![live, gone]);
+    }
+
+    #[test]
+    fn import_ids_are_rewritten_before_apply() {
+        let (catalog, old, live) = renamed_catalog();
+        let mut import: ExportData = serde_json::from_value(serde_json::json!({
+            "version": "2.1",
+            "exportedAt": "",
+            "entries": [{ "id": old.as_str(), "owned": true }],
+        }))
+        .unwrap();
+
+        let report = rewrite_import_ids(&catalog, &mut import);
+
+        assert_eq!(import.entries[0].id, live);
+        assert_eq!(report.rewritten.len(), 1);
+        assert!(report.unresolved.is_empty());
+    }
+}
@@ -1,26 +1,59 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use chrono::{NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::activity::{
+    ActivityEvent, ActivityPolicy, ActivityQuery, ActivityView, DEFAULT_ACTIVITY_LIMIT,
+};
+use crate::aliases::{rewrite_import_ids, rewrite_state_ids, IdReport};
 use crate::catalog::catalog;
-use crate::ids::{EntryId, EntryKind};
-use crate::import_export::{apply_import, export_json_from_state, ExportData};
+use crate::csv::{
+    apply_csv_import, export_csv_from_state, CsvError, CsvImportOptions, ImportReport,
+};
+use crate::events::{changed_lists, diff_states, ChangeEvent, ListenerId, Listeners};
+use crate::history::{History, HistoryResult, Step};
+use crate::ids::{normalize_for_search, EntryId, EntryKind};
+use crate::import_export::{
+    apply_import, export_json_from_state, preview_import, ExportData, ImportOptions, ImportPreview,
+    ImportSummary,
+};
 use crate::model::{
-    Catalog, Collectible, CollectibleView, CollectionStats, CollectionView, Console, ConsoleCounts,
-    ConsoleView, Game, GameView, InitialState, ItemKind, MutationResult, PersistedState,
+    Catalog, CatalogEntry, Collectible, CollectibleView, CollectionStats, CollectionView, Console,
+    ConsoleCounts, ConsoleView, EntryList, EntryState, Game, GameView, InitialState, ItemKind,
+    ItemView, MutationResult, OwnedCopy, PersistedState, PlayStatus, Region, SpendingReport,
+    TagCount,
+};
+use crate::orphans::{
+    apply_repairs, find_orphans, is_orphan, OrphanEntry, OrphanRepair, OrphanRepairSummary,
 };
 use crate::query::{
-    matches_filter, normalized_query, paginate, status_score, FilterBy, QueryInput, QueryResult,
+    cmp_play_status, cmp_release_date, matches_filter, matches_tags, paginate, parse_search,
+    status_score, FilterBy, QueryInput, QueryResult, QuerySyntaxError, SearchField, SearchTarget,
     SortKey,
 };
+use crate::spending::spending_report;
+use crate::timeline::{growth_timeline, TimelineInput, TimelineReport};
 
 #[derive(Debug, Error)]
 pub enum CoreError {
     #[error("invalid import JSON: {0}")]
     InvalidImport(#[from] serde_json::Error),
+    #[error("invalid import CSV: {0}")]
+    InvalidCsv(#[from] CsvError),
     #[error("unknown entry: {0}")]
     UnknownEntry(String),
+    #[error("unknown list: {0}")]
+    UnknownList(String),
+    #[error("invalid list name: {0:?}")]
+    InvalidListName(String),
+    #[error("not an orphaned entry: {0}")]
+    NotOrphaned(String),
+    #[error("invalid search: {0}")]
+    InvalidQuery(QuerySyntaxError),
+    #[error("timeline range spans more than {0} buckets")]
+    TimelineTooLong(usize),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,11 +75,140 @@ pub struct SetItemNotesInput {
     pub notes: String,
 }
 
+/// Sets or clears a play status. Clearing the status also clears `completed_on`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetItemPlayStatusInput {
+    pub id: EntryId,
+    #[serde(default)]
+    pub play_status: Option<PlayStatus>,
+    #[serde(default)]
+    pub completed_on: Option<NaiveDate>,
+}
+
+/// Replaces an entry's tags. Blank and duplicate tags are dropped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetItemTagsInput {
+    pub id: EntryId,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Creates the named list or replaces its members.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetListInput {
+    pub name: String,
+    #[serde(default)]
+    pub entries: Vec<EntryId>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameListInput {
+    pub name: String,
+    pub new_name: String,
+}
+
+/// Replaces every owned copy of an entry. An empty list marks it not owned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetItemCopiesInput {
+    pub id: EntryId,
+    #[serde(default)]
+    pub copies: Vec<OwnedCopy>,
+}
+
+/// Flags to set on an entry. `None` leaves a flag as it is.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StatusPatch {
+    pub owned: Option<bool>,
+    pub favorite: Option<bool>,
+    pub wishlist: Option<bool>,
+}
+
+impl From<&SetItemStatusInput> for StatusPatch {
+    fn from(input: &SetItemStatusInput) -> Self {
+        Self {
+            owned: input.owned,
+            favorite: input.favorite,
+            wishlist: input.wishlist,
+        }
+    }
+}
+
+/// A status patch for everything one tab's query matches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryPatchInput {
+    pub kind: ItemKind,
+    /// `offset` and `limit` are ignored: the patch covers every match.
+    #[serde(default)]
+    pub query: QueryInput,
+    pub patch: StatusPatch,
+    /// Count what would change without changing anything.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryPatchResult {
+    pub matched: usize,
+    /// Matches whose state changed, or would change in a dry run.
+    pub affected: usize,
+    pub dry_run: bool,
+    pub stats: CollectionStats,
+}
+
+/// One change in [`MemoryPakApp::apply_batch`], tagged by `op`:
+/// `{ "op": "status", "id": ..., "owned": true }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum BatchChange {
+    Status(SetItemStatusInput),
+    Notes(SetItemNotesInput),
+}
+
+impl BatchChange {
+    pub fn id(&self) -> &EntryId {
+        match self {
+            BatchChange::Status(input) => &input.id,
+            BatchChange::Notes(input) => &input.id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchItemResult {
+    pub id: EntryId,
+    pub state: EntryState,
+}
+
+/// Per-change results in input order, and the stats after the whole batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResult {
+    pub items: Vec<BatchItemResult>,
+    pub stats: CollectionStats,
+}
+
 #[derive(Debug)]
 pub struct MemoryPakApp {
     catalog: &'static Catalog,
     state: PersistedState,
+    id_report: IdReport,
+    /// Running aggregates, adjusted per mutation by `record_change`.
+    stats: CollectionStats,
     game_counts_by_console: HashMap<EntryId, ConsoleCounts>,
+    listeners: Listeners,
+    history: History,
+    /// Entries `update_entry` touched since the last commit, as they were
+    /// before.
+    pending: HashMap<EntryId, Option<EntryState>>,
 }
 
 impl Default for MemoryPakApp {
@@ -56,24 +218,100 @@ impl Default for MemoryPakApp {
 }
 
 impl MemoryPakApp {
-    pub fn from_persisted_state(state: PersistedState) -> Self {
+    /// Entries saved under retired ids are moved to their current id; see
+    /// [`MemoryPakApp::id_report`].
+    pub fn from_persisted_state(mut state: PersistedState) -> Self {
+        let catalog = catalog();
+        let id_report = rewrite_state_ids(catalog, &mut state);
+        for entry in state.entries.values_mut() {
+            entry.normalize_copies();
+        }
         let mut app = Self {
-            catalog: catalog(),
+            catalog,
             state,
+            id_report,
+            stats: CollectionStats::default(),
             game_counts_by_console: HashMap::new(),
+            listeners: Listeners::default(),
+            history: History::default(),
+            pending: HashMap::new(),
         };
-        app.refresh_game_counts();
+        app.rebuild_aggregates();
         app
     }
 
+    /// What loading the persisted state did to ids the catalog no longer
+    /// knows under that name.
+    pub fn id_report(&self) -> &IdReport {
+        &self.id_report
+    }
+
     pub fn persisted_state(&self) -> &PersistedState {
         &self.state
     }
 
+    /// Replaces the state with one saved elsewhere, such as by another
+    /// window, and tells listeners what differs. Undo history is cleared,
+    /// since its steps were taken against the old state.
+    pub fn reload_state(&mut self, mut state: PersistedState) {
+        self.id_report = rewrite_state_ids(self.catalog, &mut state);
+        for entry in state.entries.values_mut() {
+            entry.normalize_copies();
+        }
+        if state == self.state {
+            return;
+        }
+        let (ids, lists) = diff_states(&self.state, &state);
+        self.state = state;
+        self.history = History::default();
+        self.rebuild_aggregates();
+        self.notify(ids, lists);
+    }
+
     pub fn catalog(&self) -> &Catalog {
         self.catalog
     }
 
+    /// Calls `listener` after every call that changes the state, with the
+    /// affected ids and lists and the new stats. Failed calls send nothing.
+    pub fn subscribe(
+        &mut self,
+        listener: impl Fn(&ChangeEvent) + Send + Sync + 'static,
+    ) -> ListenerId {
+        self.listeners.add(Box::new(listener))
+    }
+
+    /// Reverts the most recent change to entries or lists. `None` when there
+    /// is nothing to undo.
+    pub fn undo(&mut self) -> Option<HistoryResult> {
+        let step = self.history.pop_undo()?;
+        let (inverse, ids, lists) = self.restore(step);
+        self.history.push_redo(inverse);
+        Some(self.history_result(ids, lists))
+    }
+
+    /// Reapplies the most recently undone change. Any new change clears
+    /// what could be redone.
+    pub fn redo(&mut self) -> Option<HistoryResult> {
+        let step = self.history.pop_redo()?;
+        let (inverse, ids, lists) = self.restore(step);
+        self.history.push_undo(inverse);
+        Some(self.history_result(ids, lists))
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Returns whether `id` was still subscribed.
+    pub fn unsubscribe(&mut self, id: ListenerId) -> bool {
+        self.listeners.remove(id)
+    }
+
     pub fn initial_state(&self) -> InitialState {
         let consoles = self.query_consoles(QueryInput::default()).items;
         let with_games: HashSet<EntryId> = self
@@ -95,24 +333,65 @@ impl MemoryPakApp {
             collections: self.collection_views(),
             total_games: self.catalog.games.len(),
             total_collectibles: self.catalog.collectibles.len(),
+            id_report: self.id_report.clone(),
+            tags: self.tag_counts(),
+            lists: self.state.lists.clone(),
+        }
+    }
+
+    pub fn tag_counts(&self) -> Vec<TagCount> {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for state in self.state.entries.values() {
+            for tag in &state.tags {
+                *counts.entry(tag.as_str()).or_default() += 1;
+            }
         }
+        counts
+            .into_iter()
+            .map(|(tag, count)| TagCount {
+                tag: tag.to_string(),
+                count,
+            })
+            .collect()
     }
 
     pub fn query_consoles(&self, input: QueryInput) -> QueryResult<ConsoleView> {
-        let search = normalized_query(input.search.as_deref());
+        let search = match parse_search(input.search.as_deref()) {
+            Ok(search) => search,
+            Err(err) => return QueryResult::invalid(err),
+        };
+        let empty = EntryState::default();
         let filter = input.filter_by.unwrap_or(FilterBy::All);
-        let sort = input.sort_by.unwrap_or(SortKey::Name);
+        let sort = input.sort_by.unwrap_or(if search.has_text() {
+            SortKey::Relevance
+        } else {
+            SortKey::Name
+        });
+        let list = self.list_members(input.list.as_deref());
 
-        let mut items: Vec<ConsoleView> = self
+        let mut items: Vec<(u32, ConsoleView)> = self
             .catalog
             .consoles
             .iter()
-            .filter(|console| matches_console_search(console, search.as_deref()))
-            .map(|console| self.console_view(console))
-            .filter(|view| matches_filter(&view.state, filter))
+            .filter(|console| {
+                list.as_ref()
+                    .is_none_or(|members| members.contains(&console.id))
+            })
+            .filter_map(|console| {
+                search
+                    .score(&ConsoleRow {
+                        console,
+                        state: self.state.entries.get(&console.id).unwrap_or(&empty),
+                    })
+                    .map(|score| (score, console))
+            })
+            .map(|(score, console)| (score, self.console_view(console)))
+            .filter(|(_, view)| matches_filter(&view.state, filter))
+            .filter(|(_, view)| matches_tags(&view.state, &input.tags))
             .collect();
 
-        items.sort_by(|a, b| match sort {
+        items.sort_by(|(a_score, a), (b_score, b)| match sort {
+            SortKey::Relevance => b_score.cmp(a_score).then_with(|| a.name.cmp(&b.name)),
             SortKey::Manufacturer => a
                 .manufacturer
                 .cmp(&b.manufacturer)
@@ -120,51 +399,116 @@ impl MemoryPakApp {
             SortKey::Status => status_score(&b.state)
                 .cmp(&status_score(&a.state))
                 .then_with(|| a.name.cmp(&b.name)),
+            SortKey::PlayStatus => {
+                cmp_play_status(&a.state, &b.state).then_with(|| a.name.cmp(&b.name))
+            }
             _ => a.name.cmp(&b.name),
         });
 
-        paginate(items, input.offset, input.limit)
+        paginate(
+            items.into_iter().map(|(_, view)| view).collect(),
+            input.offset,
+            input.limit,
+        )
     }
 
     pub fn query_games(&self, input: QueryInput) -> QueryResult<GameView> {
-        let search = normalized_query(input.search.as_deref());
+        let search = match parse_search(input.search.as_deref()) {
+            Ok(search) => search,
+            Err(err) => return QueryResult::invalid(err),
+        };
+        let empty = EntryState::default();
+        let consoles: HashMap<&EntryId, &Console> =
+            self.catalog.consoles.iter().map(|c| (&c.id, c)).collect();
         let filter = input.filter_by.unwrap_or(FilterBy::All);
-        let sort = input.sort_by.unwrap_or(SortKey::Title);
+        let sort = input.sort_by.unwrap_or(if search.has_text() {
+            SortKey::Relevance
+        } else {
+            SortKey::Title
+        });
         let console_filter = input.console_id.as_deref();
         let console_names = self.console_names_by_id();
-
-        let mut items: Vec<GameView> = self
+        let list = self.list_members(input.list.as_deref());
+        let games: Vec<&Game> = match self
             .catalog
-            .games
-            .values()
+            .search
+            .game_candidates(&search, &self.catalog.consoles)
+        {
+            Some(ids) => ids
+                .into_iter()
+                .filter_map(|id| self.catalog.games.get(id))
+                .collect(),
+            None => self.catalog.games.values().collect(),
+        };
+
+        let mut items: Vec<(u32, GameView)> = games
+            .into_iter()
             .filter(|game| match console_filter {
                 None | Some("all") | Some("") => true,
                 Some(value) => game.console_id.as_str() == value,
             })
-            .filter(|game| matches_game_search(game, search.as_deref(), &console_names))
-            .map(|game| self.game_view(game, &console_names))
-            .filter(|view| matches_filter(&view.state, filter))
+            .filter(|game| match input.region {
+                None => true,
+                Some(region) => game.releases.date_in(region).is_some(),
+            })
+            .filter(|game| {
+                list.as_ref()
+                    .is_none_or(|members| members.contains(&game.id))
+            })
+            .filter_map(|game| {
+                search
+                    .score(&GameRow {
+                        game,
+                        console: consoles.get(&game.console_id).copied(),
+                        state: self.state.entries.get(&game.id).unwrap_or(&empty),
+                    })
+                    .map(|score| (score, game))
+            })
+            .map(|(score, game)| (score, self.game_view(game, &console_names)))
+            .filter(|(_, view)| matches_filter(&view.state, filter))
+            .filter(|(_, view)| matches_tags(&view.state, &input.tags))
             .collect();
 
-        items.sort_by(|a, b| match sort {
+        items.sort_by(|(a_score, a), (b_score, b)| match sort {
+            SortKey::Relevance => b_score.cmp(a_score).then_with(|| a.title.cmp(&b.title)),
             SortKey::Year => a.year.cmp(&b.year).then_with(|| a.title.cmp(&b.title)),
+            SortKey::ReleaseDate => {
+                cmp_release_date(release_date(a, input.region), release_date(b, input.region))
+                    .then_with(|| a.title.cmp(&b.title))
+            }
             SortKey::Status => status_score(&b.state)
                 .cmp(&status_score(&a.state))
                 .then_with(|| a.title.cmp(&b.title)),
+            SortKey::PlayStatus => {
+                cmp_play_status(&a.state, &b.state).then_with(|| a.title.cmp(&b.title))
+            }
             _ => a.title.cmp(&b.title),
         });
 
-        paginate(items, input.offset, input.limit)
+        paginate(
+            items.into_iter().map(|(_, view)| view).collect(),
+            input.offset,
+            input.limit,
+        )
     }
 
     pub fn query_collectibles(&self, input: QueryInput) -> QueryResult<CollectibleView> {
-        let search = normalized_query(input.search.as_deref());
+        let search = match parse_search(input.search.as_deref()) {
+            Ok(search) => search,
+            Err(err) => return QueryResult::invalid(err),
+        };
+        let empty = EntryState::default();
         let filter = input.filter_by.unwrap_or(FilterBy::All);
-        let sort = input.sort_by.unwrap_or(SortKey::Name);
+        let sort = input.sort_by.unwrap_or(if search.has_text() {
+            SortKey::Relevance
+        } else {
+            SortKey::Name
+        });
         let collection_filter = input.collection_id.as_deref();
         let collection_names = self.collection_names_by_id();
+        let list = self.list_members(input.list.as_deref());
 
-        let mut items: Vec<CollectibleView> = self
+        let mut items: Vec<(u32, CollectibleView)> = self
             .catalog
             .collectibles
             .iter()
@@ -172,12 +516,28 @@ impl MemoryPakApp {
                 None | Some("all") | Some("") => true,
                 Some(value) => item.collection_id == value,
             })
-            .filter(|item| matches_collectible_search(item, search.as_deref(), &collection_names))
-            .map(|item| self.collectible_view(item, &collection_names))
-            .filter(|view| matches_filter(&view.state, filter))
+            .filter(|item| {
+                list.as_ref()
+                    .is_none_or(|members| members.contains(&item.id))
+            })
+            .filter_map(|item| {
+                search
+                    .score(&CollectibleRow {
+                        item,
+                        collection_name: collection_names
+                            .get(&item.collection_id)
+                            .map(String::as_str),
+                        state: self.state.entries.get(&item.id).unwrap_or(&empty),
+                    })
+                    .map(|score| (score, item))
+            })
+            .map(|(score, item)| (score, self.collectible_view(item, &collection_names)))
+            .filter(|(_, view)| matches_filter(&view.state, filter))
+            .filter(|(_, view)| matches_tags(&view.state, &input.tags))
             .collect();
 
-        items.sort_by(|a, b| match sort {
+        items.sort_by(|(a_score, a), (b_score, b)| match sort {
+            SortKey::Relevance => b_score.cmp(a_score).then_with(|| a.name.cmp(&b.name)),
             SortKey::Collection => a
                 .collection_name
                 .cmp(&b.collection_name)
@@ -192,36 +552,184 @@ impl MemoryPakApp {
             SortKey::Status => status_score(&b.state)
                 .cmp(&status_score(&a.state))
                 .then_with(|| a.name.cmp(&b.name)),
+            SortKey::PlayStatus => {
+                cmp_play_status(&a.state, &b.state).then_with(|| a.name.cmp(&b.name))
+            }
             _ => a.name.cmp(&b.name),
         });
 
-        paginate(items, input.offset, input.limit)
+        paginate(
+            items.into_iter().map(|(_, view)| view).collect(),
+            input.offset,
+            input.limit,
+        )
+    }
+
+    /// The full view of one entry, for detail screens.
+    pub fn get_item(&self, id: &EntryId) -> Result<ItemView, CoreError> {
+        let entry = self
+            .catalog
+            .get(id)
+            .ok_or_else(|| CoreError::UnknownEntry(id.as_str().to_string()))?;
+        Ok(match entry {
+            CatalogEntry::Console(console) => ItemView::Console(self.console_view(console)),
+            CatalogEntry::Game(game) => {
+                ItemView::Game(self.game_view(game, &self.console_names_by_id()))
+            }
+            CatalogEntry::Collectible(item) => {
+                ItemView::Collectible(self.collectible_view(item, &self.collection_names_by_id()))
+            }
+        })
     }
 
+    /// Toggles flags on an entry. `owned: true` adds a default copy when
+    /// none is recorded yet; `owned: false` removes every copy.
     pub fn set_item_status(
         &mut self,
         input: SetItemStatusInput,
     ) -> Result<MutationResult, CoreError> {
         let kind = self.ensure_entry(&input.id)?;
 
-        let entry = self.state.entries.entry(input.id.clone()).or_default();
-        if let Some(value) = input.owned {
-            entry.owned = value;
+        let snapshot = self.update_entry(&input.id, kind, |entry| {
+            apply_status(entry, &StatusPatch::from(&input))
+        });
+
+        self.commit_entries();
+        Ok(MutationResult {
+            id: input.id,
+            state: snapshot,
+            stats: self.collection_stats(),
+        })
+    }
+
+    pub fn set_item_notes(
+        &mut self,
+        input: SetItemNotesInput,
+    ) -> Result<MutationResult, CoreError> {
+        let kind = self.ensure_entry(&input.id)?;
+
+        let snapshot = self.update_entry(&input.id, kind, |entry| entry.notes = input.notes);
+
+        self.commit_entries();
+        Ok(MutationResult {
+            id: input.id,
+            state: snapshot,
+            stats: self.collection_stats(),
+        })
+    }
+
+    /// Applies every change in order, or none of them if any id is
+    /// unknown. Each item's state is the one right after its own change;
+    /// listeners get a single event and `stats` is taken once at the end.
+    pub fn apply_batch(&mut self, changes: Vec<BatchChange>) -> Result<BatchResult, CoreError> {
+        let kinds = changes
+            .iter()
+            .map(|change| self.ensure_entry(change.id()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut items = Vec::with_capacity(changes.len());
+        for (change, kind) in changes.into_iter().zip(kinds) {
+            let (id, state) = match change {
+                BatchChange::Status(input) => {
+                    let state = self.update_entry(&input.id, kind, |entry| {
+                        apply_status(entry, &StatusPatch::from(&input))
+                    });
+                    (input.id, state)
+                }
+                BatchChange::Notes(input) => {
+                    let state =
+                        self.update_entry(&input.id, kind, |entry| entry.notes = input.notes);
+                    (input.id, state)
+                }
+            };
+            items.push(BatchItemResult { id, state });
         }
-        if let Some(value) = input.favorite {
-            entry.favorite = value;
+
+        self.commit_entries();
+        Ok(BatchResult {
+            items,
+            stats: self.collection_stats(),
+        })
+    }
+
+    /// Applies `patch` to every entry the query matches, or only counts
+    /// them with `dry_run`. Matches the patch would not change are left
+    /// alone and not counted as affected.
+    pub fn apply_status_to_query(
+        &mut self,
+        input: QueryPatchInput,
+    ) -> Result<QueryPatchResult, CoreError> {
+        let query = QueryInput {
+            offset: None,
+            limit: None,
+            ..input.query
+        };
+        let ids = match input.kind {
+            ItemKind::Console => matched_ids(self.query_consoles(query), |view| view.id)?,
+            ItemKind::Game => matched_ids(self.query_games(query), |view| view.id)?,
+            ItemKind::Collectible => matched_ids(self.query_collectibles(query), |view| view.id)?,
+        };
+
+        let mut changed = Vec::new();
+        for id in &ids {
+            let current = self.state.entries.get(id).cloned().unwrap_or_default();
+            let mut next = current.clone();
+            apply_status(&mut next, &input.patch);
+            if next != current {
+                changed.push(id.clone());
+            }
         }
-        if let Some(value) = input.wishlist {
-            entry.wishlist = value;
+
+        let affected = changed.len();
+        if !input.dry_run && affected > 0 {
+            for id in &changed {
+                let kind = self.ensure_entry(id)?;
+                self.update_entry(id, kind, |entry| apply_status(entry, &input.patch));
+            }
+            self.commit_entries();
         }
-        let snapshot = entry.clone();
 
-        if kind == EntryKind::Game {
-            self.refresh_game_counts();
+        Ok(QueryPatchResult {
+            matched: ids.len(),
+            affected,
+            dry_run: input.dry_run,
+            stats: self.collection_stats(),
+        })
+    }
+
+    pub fn set_item_play_status(
+        &mut self,
+        input: SetItemPlayStatusInput,
+    ) -> Result<MutationResult, CoreError> {
+        let kind = self.ensure_entry(&input.id)?;
+
+        let snapshot = self.update_entry(&input.id, kind, |entry| {
+            entry.play_status = input.play_status;
+            entry.completed_on = input.play_status.and(input.completed_on);
+        });
+
+        self.commit_entries();
+        Ok(MutationResult {
+            id: input.id,
+            state: snapshot,
+            stats: self.collection_stats(),
+        })
+    }
+
+    pub fn set_item_tags(&mut self, input: SetItemTagsInput) -> Result<MutationResult, CoreError> {
+        let kind = self.ensure_entry(&input.id)?;
+
+        let mut tags: Vec<String> = Vec::with_capacity(input.tags.len());
+        for tag in input.tags {
+            let tag = tag.trim();
+            if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+                tags.push(tag.to_string());
+            }
         }
 
-        self.cleanup_empty(&input.id);
+        let snapshot = self.update_entry(&input.id, kind, |entry| entry.tags = tags);
 
+        self.commit_entries();
         Ok(MutationResult {
             id: input.id,
             state: snapshot,
@@ -229,17 +737,72 @@ impl MemoryPakApp {
         })
     }
 
-    pub fn set_item_notes(
+    /// Creates or replaces a list. Unknown ids are rejected; duplicates keep
+    /// their first position.
+    pub fn set_list(&mut self, input: SetListInput) -> Result<EntryList, CoreError> {
+        let name = valid_list_name(&input.name)?;
+        let mut entries: Vec<EntryId> = Vec::with_capacity(input.entries.len());
+        for id in input.entries {
+            self.ensure_entry(&id)?;
+            if !entries.contains(&id) {
+                entries.push(id);
+            }
+        }
+
+        let list = EntryList { name, entries };
+        let before = self.state.lists.clone();
+        match self.state.lists.iter_mut().find(|l| l.name == list.name) {
+            Some(existing) => *existing = list.clone(),
+            None => self.state.lists.push(list.clone()),
+        }
+        self.commit_lists(before, vec![list.name.clone()]);
+        Ok(list)
+    }
+
+    pub fn rename_list(&mut self, input: RenameListInput) -> Result<EntryList, CoreError> {
+        let new_name = valid_list_name(&input.new_name)?;
+        if new_name != input.name && self.state.lists.iter().any(|l| l.name == new_name) {
+            return Err(CoreError::InvalidListName(new_name));
+        }
+        let before = self.state.lists.clone();
+        let list = self
+            .state
+            .lists
+            .iter_mut()
+            .find(|l| l.name == input.name)
+            .ok_or_else(|| CoreError::UnknownList(input.name.clone()))?;
+        list.name = new_name;
+        let list = list.clone();
+        let mut names = vec![input.name, list.name.clone()];
+        names.sort();
+        names.dedup();
+        self.commit_lists(before, names);
+        Ok(list)
+    }
+
+    pub fn delete_list(&mut self, name: &str) -> Result<(), CoreError> {
+        if !self.state.lists.iter().any(|l| l.name == name) {
+            return Err(CoreError::UnknownList(name.to_string()));
+        }
+        let before = self.state.lists.clone();
+        self.state.lists.retain(|l| l.name != name);
+        self.commit_lists(before, vec![name.to_string()]);
+        Ok(())
+    }
+
+    pub fn set_item_copies(
         &mut self,
-        input: SetItemNotesInput,
+        input: SetItemCopiesInput,
     ) -> Result<MutationResult, CoreError> {
-        self.ensure_entry(&input.id)?;
+        let kind = self.ensure_entry(&input.id)?;
 
-        let entry = self.state.entries.entry(input.id.clone()).or_default();
-        entry.notes = input.notes;
-        let snapshot = entry.clone();
-        self.cleanup_empty(&input.id);
+        let snapshot = self.update_entry(&input.id, kind, |entry| {
+            entry.copies = input.copies;
+            entry.owned = false;
+            entry.normalize_copies();
+        });
 
+        self.commit_entries();
         Ok(MutationResult {
             id: input.id,
             state: snapshot,
@@ -247,66 +810,176 @@ impl MemoryPakApp {
         })
     }
 
-    pub fn import_json(&mut self, json: &str) -> Result<CollectionStats, CoreError> {
-        let import = serde_json::from_str::<ExportData>(json)?;
-        apply_import(&mut self.state, import);
-        self.state.entries.retain(|_, state| !state.is_empty());
-        self.refresh_game_counts();
-        Ok(self.collection_stats())
+    /// Reports what `import_json` would change with `options`, without
+    /// applying anything.
+    pub fn preview_import_json(
+        &self,
+        json: &str,
+        options: ImportOptions,
+    ) -> Result<ImportPreview, CoreError> {
+        let mut import = serde_json::from_str::<ExportData>(json)?;
+        let ids = rewrite_import_ids(self.catalog, &mut import);
+        let mut preview = preview_import(self.catalog, &self.state, &import, options);
+        preview.renamed = ids.rewritten;
+        Ok(preview)
     }
 
-    pub fn export_json(&self) -> Result<String, serde_json::Error> {
-        export_json_from_state(&self.state)
-    }
+    pub fn import_json(
+        &mut self,
+        json: &str,
+        options: ImportOptions,
+    ) -> Result<ImportSummary, CoreError> {
+        let mut import = serde_json::from_str::<ExportData>(json)?;
+        let ids = rewrite_import_ids(self.catalog, &mut import);
+        let before = self.state.clone();
+        apply_import(&mut self.state, import, options);
+        self.finish_import();
 
-    pub fn collection_stats(&self) -> CollectionStats {
-        let mut stats = CollectionStats {
-            total_consoles: self.catalog.consoles.len(),
-            total_games: self.catalog.games.len(),
-            total_collectibles: self.catalog.collectibles.len(),
-            ..CollectionStats::default()
+        let mut summary = ImportSummary {
+            stats: self.collection_stats(),
+            renamed: ids.rewritten,
+            unresolved: ids.unresolved,
+            ..ImportSummary::default()
         };
-
         for (id, state) in &self.state.entries {
-            match id.kind() {
-                Some(EntryKind::Console) => {
-                    if state.owned {
-                        stats.owned_consoles += 1;
-                    }
-                    if state.favorite {
-                        stats.favorite_consoles += 1;
-                    }
-                    if state.wishlist {
-                        stats.wishlist_consoles += 1;
-                    }
-                }
-                Some(EntryKind::Game) => {
-                    if state.owned {
-                        stats.owned_games += 1;
-                    }
-                    if state.favorite {
-                        stats.favorite_games += 1;
-                    }
-                    if state.wishlist {
-                        stats.wishlist_games += 1;
-                    }
-                }
-                Some(EntryKind::Collectible) => {
-                    if state.owned {
-                        stats.owned_collectibles += 1;
-                    }
-                    if state.favorite {
-                        stats.favorite_collectibles += 1;
-                    }
-                    if state.wishlist {
-                        stats.wishlist_collectibles += 1;
-                    }
-                }
-                None => {}
+            match before.entries.get(id) {
+                None => summary.added += 1,
+                Some(old) if old != state => summary.updated += 1,
+                Some(_) => {}
+            }
+        }
+        summary.removed = before
+            .entries
+            .keys()
+            .filter(|id| !self.state.entries.contains_key(*id))
+            .count();
+        self.commit_since(before);
+        Ok(summary)
+    }
+
+    /// Imports a CSV, matching rows by id or by title and console. Rows
+    /// that match nothing, or carry invalid values, are skipped and listed
+    /// in the report.
+    pub fn import_csv(
+        &mut self,
+        csv: &str,
+        options: CsvImportOptions,
+    ) -> Result<ImportReport, CoreError> {
+        let before = self.state.clone();
+        let (matched, unmatched) = apply_csv_import(self.catalog, &mut self.state, csv, &options)?;
+        self.finish_import();
+        self.commit_since(before);
+        Ok(ImportReport {
+            stats: self.collection_stats(),
+            matched,
+            unmatched,
+        })
+    }
+
+    /// Saved entries and list members whose ids the catalog does not have,
+    /// with likely replacements.
+    pub fn orphans(&self) -> Vec<OrphanEntry> {
+        find_orphans(self.catalog, &self.state)
+    }
+
+    /// Remaps or discards orphaned ids. Every repair is checked before any
+    /// is applied, so one bad id leaves the state untouched.
+    pub fn repair_orphans(
+        &mut self,
+        repairs: Vec<OrphanRepair>,
+    ) -> Result<OrphanRepairSummary, CoreError> {
+        let mut seen = HashSet::new();
+        for repair in &repairs {
+            let id = repair.id();
+            if !seen.insert(id) || !is_orphan(self.catalog, &self.state, id) {
+                return Err(CoreError::NotOrphaned(id.as_str().to_string()));
+            }
+            if let OrphanRepair::Remap { to, .. } = repair {
+                self.ensure_entry(to)?;
             }
         }
 
-        stats
+        let before = self.state.clone();
+        let (remapped, discarded) = apply_repairs(&mut self.state, repairs);
+        self.finish_import();
+        self.commit_since(before);
+        Ok(OrphanRepairSummary {
+            stats: self.collection_stats(),
+            remapped,
+            discarded,
+        })
+    }
+
+    pub fn export_json(&self) -> Result<String, serde_json::Error> {
+        export_json_from_state(self.catalog, &self.state)
+    }
+
+    /// Every tracked entry as CSV, joined with its catalog metadata.
+    pub fn export_csv(&self) -> String {
+        export_csv_from_state(self.catalog, &self.state)
+    }
+
+    /// The newest activity first.
+    pub fn recent_activity(&self, query: ActivityQuery) -> Vec<ActivityView> {
+        self.state
+            .activity
+            .events
+            .iter()
+            .rev()
+            .filter(|event| query.since.is_none_or(|since| event.at >= since))
+            .take(query.limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT))
+            .map(|event| ActivityView {
+                at: event.at,
+                id: event.id.clone(),
+                name: self
+                    .catalog
+                    .get(&event.id)
+                    .map(|entry| entry.name().to_string()),
+                change: event.change.clone(),
+            })
+            .collect()
+    }
+
+    /// Everything recorded for one entry, oldest first.
+    pub fn entry_history(&self, id: &EntryId) -> Vec<ActivityEvent> {
+        self.state
+            .activity
+            .events
+            .iter()
+            .filter(|event| &event.id == id)
+            .cloned()
+            .collect()
+    }
+
+    pub fn activity_policy(&self) -> Option<ActivityPolicy> {
+        self.state.activity.policy
+    }
+
+    /// Sets how much activity is kept and compacts the log right away.
+    /// Returns how many events were dropped.
+    pub fn set_activity_policy(&mut self, policy: Option<ActivityPolicy>) -> usize {
+        let changed = self.state.activity.policy != policy;
+        self.state.activity.policy = policy;
+        let dropped = self.state.activity.compact(Utc::now());
+        if changed || dropped > 0 {
+            self.notify(Vec::new(), Vec::new());
+        }
+        dropped
+    }
+
+    pub fn spending_report(&self) -> SpendingReport {
+        spending_report(self.catalog, &self.state)
+    }
+
+    /// Owned counts and spending per week, month or year, in total and per
+    /// console and collection. Fails when the range needs more than
+    /// [`crate::MAX_TIMELINE_BUCKETS`] buckets.
+    pub fn growth_timeline(&self, input: TimelineInput) -> Result<TimelineReport, CoreError> {
+        growth_timeline(self.catalog, &self.state, input)
+    }
+
+    pub fn collection_stats(&self) -> CollectionStats {
+        self.stats.clone()
     }
 
     fn collection_views(&self) -> Vec<CollectionView> {
@@ -340,19 +1013,10 @@ impl MemoryPakApp {
     }
 
     fn ensure_entry(&self, id: &EntryId) -> Result<EntryKind, CoreError> {
-        let kind = id
-            .kind()
-            .ok_or_else(|| CoreError::UnknownEntry(id.as_str().to_string()))?;
-        let exists = match kind {
-            EntryKind::Console => self.catalog.consoles.iter().any(|c| &c.id == id),
-            EntryKind::Game => self.catalog.games.contains_key(id),
-            EntryKind::Collectible => self.catalog.collectibles.iter().any(|c| &c.id == id),
-        };
-        if exists {
-            Ok(kind)
-        } else {
-            Err(CoreError::UnknownEntry(id.as_str().to_string()))
-        }
+        self.catalog
+            .get(id)
+            .map(|entry| entry.kind())
+            .ok_or_else(|| CoreError::UnknownEntry(id.as_str().to_string()))
     }
 
     fn cleanup_empty(&mut self, id: &EntryId) {
@@ -363,27 +1027,245 @@ impl MemoryPakApp {
         }
     }
 
-    fn refresh_game_counts(&mut self) {
+    /// Ends a change made through `update_entry`: records what it
+    /// overwrote for undo and tells listeners about the entries that
+    /// actually changed.
+    fn commit_entries(&mut self) {
+        // Once per change rather than per entry, so bulk changes stay linear.
+        #[cfg(debug_assertions)]
+        self.assert_aggregates_consistent();
+
+        let mut entries: Vec<_> = self
+            .pending
+            .drain()
+            .filter(|(id, before)| self.state.entries.get(id) != before.as_ref())
+            .collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let ids: Vec<EntryId> = entries.iter().map(|(id, _)| id.clone()).collect();
+        self.log_activity(&entries);
+        self.history.push_change(Step {
+            entries,
+            lists: None,
+        });
+        if !ids.is_empty() {
+            self.notify(ids, Vec::new());
+        }
+    }
+
+    /// Like `commit_entries`, a change that left the lists as they were
+    /// records and announces nothing.
+    fn commit_lists(&mut self, before: Vec<EntryList>, names: Vec<String>) {
+        if before == self.state.lists {
+            return;
+        }
+        self.history.push_change(Step {
+            entries: Vec::new(),
+            lists: Some(before),
+        });
+        self.notify(Vec::new(), names);
+    }
+
+    /// Ends a bulk change by diffing against the state before it.
+    fn commit_since(&mut self, mut before: PersistedState) {
+        let (ids, lists) = diff_states(&before, &self.state);
+        let entries: Vec<_> = ids
+            .iter()
+            .map(|id| (id.clone(), before.entries.remove(id)))
+            .collect();
+        self.log_activity(&entries);
+        self.history.push_change(Step {
+            entries,
+            lists: (!lists.is_empty()).then_some(before.lists),
+        });
+        if !ids.is_empty() || !lists.is_empty() {
+            self.notify(ids, lists);
+        }
+    }
+
+    /// Puts back what `step` recorded and returns the step that reverses
+    /// that, with the ids and lists it touched.
+    fn restore(&mut self, step: Step) -> (Step, Vec<EntryId>, Vec<String>) {
+        let empty = EntryState::default();
+        let mut inverse = Step::default();
+        let mut ids = Vec::with_capacity(step.entries.len());
+        for (id, state) in step.entries {
+            let current = match state {
+                Some(state) => self.state.entries.insert(id.clone(), state),
+                None => self.state.entries.remove(&id),
+            };
+            if let Some(kind) = self.catalog.get(&id).map(|entry| entry.kind()) {
+                let after = self.state.entries.get(&id).unwrap_or(&empty).clone();
+                self.record_change(&id, kind, current.as_ref().unwrap_or(&empty), &after);
+            }
+            inverse.entries.push((id.clone(), current));
+            ids.push(id);
+        }
+        #[cfg(debug_assertions)]
+        self.assert_aggregates_consistent();
+        self.log_activity(&inverse.entries);
+        let mut lists = Vec::new();
+        if let Some(restored) = step.lists {
+            let current = std::mem::replace(&mut self.state.lists, restored);
+            lists = changed_lists(&current, &self.state.lists);
+            inverse.lists = Some(current);
+        }
+        ids.sort();
+        self.notify(ids.clone(), lists.clone());
+        (inverse, ids, lists)
+    }
+
+    /// Appends activity for entries that changed from the given states to
+    /// their current ones, then applies the compaction policy.
+    fn log_activity(&mut self, before: &[(EntryId, Option<EntryState>)]) {
+        let now = Utc::now();
+        let empty = EntryState::default();
+        for (id, old) in before {
+            let new = self.state.entries.get(id).unwrap_or(&empty);
+            self.state
+                .activity
+                .record(id, old.as_ref().unwrap_or(&empty), new, now);
+        }
+        self.state.activity.compact(now);
+    }
+
+    fn history_result(&self, ids: Vec<EntryId>, lists: Vec<String>) -> HistoryResult {
+        HistoryResult {
+            items: ids
+                .into_iter()
+                .map(|id| BatchItemResult {
+                    state: self.state.entries.get(&id).cloned().unwrap_or_default(),
+                    id,
+                })
+                .collect(),
+            lists,
+            stats: self.collection_stats(),
+            can_undo: self.history.can_undo(),
+            can_redo: self.history.can_redo(),
+        }
+    }
+
+    fn notify(&self, ids: Vec<EntryId>, lists: Vec<String>) {
+        if self.listeners.is_empty() {
+            return;
+        }
+        self.listeners.emit(&ChangeEvent {
+            ids,
+            lists,
+            stats: self.collection_stats(),
+        });
+    }
+
+    fn finish_import(&mut self) {
+        for entry in self.state.entries.values_mut() {
+            entry.normalize_copies();
+        }
+        self.state.entries.retain(|_, state| !state.is_empty());
+        self.rebuild_aggregates();
+    }
+
+    /// Applies `update` to one entry, drops it again if it ends up empty,
+    /// and adjusts the running aggregates by the difference.
+    fn update_entry(
+        &mut self,
+        id: &EntryId,
+        kind: EntryKind,
+        update: impl FnOnce(&mut EntryState),
+    ) -> EntryState {
+        self.pending
+            .entry(id.clone())
+            .or_insert_with(|| self.state.entries.get(id).cloned());
+        let entry = self.state.entries.entry(id.clone()).or_default();
+        let before = entry.clone();
+        update(entry);
+        let snapshot = entry.clone();
+        self.cleanup_empty(id);
+        self.record_change(id, kind, &before, &snapshot);
+        snapshot
+    }
+
+    /// Moves one entry's contribution to the aggregates from `before` to
+    /// `after`.
+    fn record_change(
+        &mut self,
+        id: &EntryId,
+        kind: EntryKind,
+        before: &EntryState,
+        after: &EntryState,
+    ) {
+        tally_stats(&mut self.stats, kind, before, false);
+        tally_stats(&mut self.stats, kind, after, true);
+
+        if let Some(game) = self.catalog.games.get(id) {
+            let counts = self
+                .game_counts_by_console
+                .entry(game.console_id.clone())
+                .or_default();
+            tally_console(counts, before, false);
+            tally_console(counts, after, true);
+            if *counts == ConsoleCounts::default() {
+                self.game_counts_by_console.remove(&game.console_id);
+            }
+        }
+    }
+
+    /// Recomputes every aggregate from scratch; used on load and import.
+    fn rebuild_aggregates(&mut self) {
+        (self.stats, self.game_counts_by_console) = self.full_aggregates();
+    }
+
+    fn full_aggregates(&self) -> (CollectionStats, HashMap<EntryId, ConsoleCounts>) {
+        let mut stats = CollectionStats {
+            total_consoles: self.catalog.consoles.len(),
+            total_games: self.catalog.games.len(),
+            total_collectibles: self.catalog.collectibles.len(),
+            ..CollectionStats::default()
+        };
         let mut counts: HashMap<EntryId, ConsoleCounts> = HashMap::new();
+
         for (id, state) in &self.state.entries {
-            if id.kind() != Some(EntryKind::Game) {
-                continue;
-            }
-            let Some(game) = self.catalog.games.get(id) else {
+            // Orphans stay saved but are not part of the collection.
+            let Some(kind) = self.catalog.get(id).map(|entry| entry.kind()) else {
                 continue;
             };
-            let entry = counts.entry(game.console_id.clone()).or_default();
-            if state.owned {
-                entry.owned += 1;
-            }
-            if state.favorite {
-                entry.favorite += 1;
-            }
-            if state.wishlist {
-                entry.wishlist += 1;
+            tally_stats(&mut stats, kind, state, true);
+            if let Some(game) = self.catalog.games.get(id) {
+                tally_console(
+                    counts.entry(game.console_id.clone()).or_default(),
+                    state,
+                    true,
+                );
             }
         }
-        self.game_counts_by_console = counts;
+        counts.retain(|_, c| *c != ConsoleCounts::default());
+
+        (stats, counts)
+    }
+
+    #[cfg(debug_assertions)]
+    fn assert_aggregates_consistent(&self) {
+        let (stats, counts) = self.full_aggregates();
+        debug_assert_eq!(
+            self.stats, stats,
+            "running stats drifted from a full recount"
+        );
+        debug_assert_eq!(
+            self.game_counts_by_console, counts,
+            "running console counts drifted from a full recount"
+        );
+    }
+
+    /// Members of the named list. An unknown name yields an empty set so the
+    /// query matches nothing rather than everything.
+    fn list_members(&self, name: Option<&str>) -> Option<HashSet<&EntryId>> {
+        let name = name?;
+        Some(
+            self.state
+                .lists
+                .iter()
+                .find(|l| l.name == name)
+                .map(|l| l.entries.iter().collect())
+                .unwrap_or_default(),
+        )
     }
 
     fn console_names_by_id(&self) -> HashMap<EntryId, String> {
@@ -438,6 +1320,7 @@ impl MemoryPakApp {
                 .get(&game.console_id)
                 .cloned()
                 .unwrap_or_else(|| game.console_short_id.clone()),
+            releases: game.releases.clone(),
             state: self
                 .state
                 .entries
@@ -475,40 +1358,226 @@ impl MemoryPakApp {
     }
 }
 
-fn matches_console_search(console: &Console, query: Option<&str>) -> bool {
-    let Some(q) = query else { return true };
-    crate::ids::normalize_for_search(&console.name).contains(q)
-        || crate::ids::normalize_for_search(&console.manufacturer).contains(q)
-        || crate::ids::normalize_for_search(&console.abbreviation).contains(q)
-}
-
-fn matches_game_search(
-    game: &Game,
-    query: Option<&str>,
-    console_names: &HashMap<EntryId, String>,
-) -> bool {
-    let Some(q) = query else { return true };
-    crate::ids::normalize_for_search(&game.title).contains(q)
-        || crate::ids::normalize_for_search(&game.publisher).contains(q)
-        || crate::ids::normalize_for_search(&game.developer).contains(q)
-        || console_names
-            .get(&game.console_id)
-            .map(|name| crate::ids::normalize_for_search(name).contains(q))
-            .unwrap_or(false)
-}
-
-fn matches_collectible_search(
-    item: &Collectible,
-    query: Option<&str>,
-    collection_names: &HashMap<String, String>,
-) -> bool {
-    let Some(q) = query else { return true };
-    crate::ids::normalize_for_search(&item.name).contains(q)
-        || crate::ids::normalize_for_search(&item.category).contains(q)
-        || crate::ids::normalize_for_search(&item.group).contains(q)
-        || crate::ids::normalize_for_search(&item.variant).contains(q)
-        || collection_names
-            .get(&item.collection_id)
-            .map(|name| crate::ids::normalize_for_search(name).contains(q))
-            .unwrap_or(false)
+/// `owned: true` adds a default copy when none is recorded yet;
+/// `owned: false` removes every copy.
+fn apply_status(entry: &mut EntryState, patch: &StatusPatch) {
+    if let Some(value) = patch.owned {
+        entry.owned = value;
+        if !value {
+            entry.copies.clear();
+        }
+        entry.normalize_copies();
+    }
+    if let Some(value) = patch.favorite {
+        entry.favorite = value;
+    }
+    if let Some(value) = patch.wishlist {
+        entry.wishlist = value;
+    }
+}
+
+/// Ids of every match, or the search's syntax error.
+fn matched_ids<T>(
+    result: QueryResult<T>,
+    id: impl Fn(T) -> EntryId,
+) -> Result<Vec<EntryId>, CoreError> {
+    match result.error {
+        Some(err) => Err(CoreError::InvalidQuery(err)),
+        None => Ok(result.items.into_iter().map(id).collect()),
+    }
+}
+
+/// Adds or removes one entry's share of the collection totals.
+fn tally_stats(stats: &mut CollectionStats, kind: EntryKind, state: &EntryState, add: bool) {
+    let (owned, copies, favorite, wishlist) = match kind {
+        EntryKind::Console => (
+            &mut stats.owned_consoles,
+            &mut stats.owned_console_copies,
+            &mut stats.favorite_consoles,
+            &mut stats.wishlist_consoles,
+        ),
+        EntryKind::Game => (
+            &mut stats.owned_games,
+            &mut stats.owned_game_copies,
+            &mut stats.favorite_games,
+            &mut stats.wishlist_games,
+        ),
+        EntryKind::Collectible => (
+            &mut stats.owned_collectibles,
+            &mut stats.owned_collectible_copies,
+            &mut stats.favorite_collectibles,
+            &mut stats.wishlist_collectibles,
+        ),
+    };
+    bump(owned, usize::from(state.owned), add);
+    bump(copies, state.copies.len(), add);
+    bump(favorite, usize::from(state.favorite), add);
+    bump(wishlist, usize::from(state.wishlist), add);
+}
+
+/// Adds or removes one game's share of its console's counts.
+fn tally_console(counts: &mut ConsoleCounts, state: &EntryState, add: bool) {
+    bump(&mut counts.owned, usize::from(state.owned), add);
+    bump(&mut counts.owned_copies, state.copies.len(), add);
+    bump(&mut counts.favorite, usize::from(state.favorite), add);
+    bump(&mut counts.wishlist, usize::from(state.wishlist), add);
+    bump(
+        &mut counts.backlog,
+        usize::from(state.play_status == Some(PlayStatus::Backlog)),
+        add,
+    );
+}
+
+fn bump(count: &mut usize, by: usize, add: bool) {
+    if add {
+        *count += by;
+    } else {
+        *count -= by;
+    }
+}
+
+fn valid_list_name(name: &str) -> Result<String, CoreError> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        Err(CoreError::InvalidListName(name.to_string()))
+    } else {
+        Ok(trimmed.to_string())
+    }
+}
+
+fn release_date(view: &GameView, region: Option<Region>) -> Option<&str> {
+    match region {
+        Some(region) => view.releases.date_in(region),
+        None => view.releases.earliest(),
+    }
+}
+
+struct ConsoleRow<'a> {
+    console: &'a Console,
+    state: &'a EntryState,
+}
+
+impl SearchTarget for ConsoleRow<'_> {
+    fn visit_text(&self, field: Option<SearchField>, visit: &mut dyn FnMut(&str, bool)) {
+        let c = self.console;
+        let mut emit = |value: &str, primary| visit(&normalize_for_search(value), primary);
+        match field {
+            None => {
+                emit(&c.name, true);
+                emit(&c.manufacturer, false);
+                emit(&c.abbreviation, false);
+            }
+            Some(SearchField::Title) => emit(&c.name, true),
+            Some(SearchField::Console) => {
+                emit(&c.name, true);
+                emit(&c.short_id, false);
+                emit(&c.abbreviation, false);
+            }
+            Some(SearchField::Manufacturer) => emit(&c.manufacturer, false),
+            Some(_) => {}
+        }
+    }
+
+    fn year(&self) -> Option<u32> {
+        None
+    }
+
+    fn state(&self) -> &EntryState {
+        self.state
+    }
+}
+
+struct GameRow<'a> {
+    game: &'a Game,
+    console: Option<&'a Console>,
+    state: &'a EntryState,
+}
+
+impl SearchTarget for GameRow<'_> {
+    fn visit_text(&self, field: Option<SearchField>, visit: &mut dyn FnMut(&str, bool)) {
+        let g = self.game;
+        let mut emit = |value: &str, primary| visit(&normalize_for_search(value), primary);
+        match field {
+            None => {
+                emit(&g.title, true);
+                emit(&g.publisher, false);
+                emit(&g.developer, false);
+                if let Some(c) = self.console {
+                    emit(&c.name, false);
+                }
+            }
+            Some(SearchField::Title) => emit(&g.title, true),
+            Some(SearchField::Publisher) => emit(&g.publisher, false),
+            Some(SearchField::Developer) => emit(&g.developer, false),
+            Some(SearchField::Console) => match self.console {
+                Some(c) => {
+                    emit(&c.name, false);
+                    emit(&c.short_id, false);
+                    emit(&c.abbreviation, false);
+                }
+                None => emit(&g.console_short_id, false),
+            },
+            Some(SearchField::Manufacturer) => {
+                if let Some(c) = self.console {
+                    emit(&c.manufacturer, false);
+                }
+            }
+            Some(_) => {}
+        }
+    }
+
+    fn year(&self) -> Option<u32> {
+        (self.game.year != 0).then_some(self.game.year)
+    }
+
+    fn released_in(&self, region: Region) -> bool {
+        self.game.releases.date_in(region).is_some()
+    }
+
+    fn state(&self) -> &EntryState {
+        self.state
+    }
+}
+
+struct CollectibleRow<'a> {
+    item: &'a Collectible,
+    collection_name: Option<&'a str>,
+    state: &'a EntryState,
+}
+
+impl SearchTarget for CollectibleRow<'_> {
+    fn visit_text(&self, field: Option<SearchField>, visit: &mut dyn FnMut(&str, bool)) {
+        let c = self.item;
+        let mut emit = |value: &str, primary| visit(&normalize_for_search(value), primary);
+        match field {
+            None => {
+                emit(&c.name, true);
+                emit(&c.category, false);
+                emit(&c.group, false);
+                emit(&c.variant, false);
+                if let Some(name) = self.collection_name {
+                    emit(name, false);
+                }
+            }
+            Some(SearchField::Title) => emit(&c.name, true),
+            Some(SearchField::Collection) => {
+                emit(&c.collection_id, false);
+                if let Some(name) = self.collection_name {
+                    emit(name, false);
+                }
+            }
+            Some(SearchField::Category) => emit(&c.category, false),
+            Some(SearchField::Group) => emit(&c.group, false),
+            Some(SearchField::Variant) => emit(&c.variant, false),
+            Some(_) => {}
+        }
+    }
+
+    fn year(&self) -> Option<u32> {
+        (self.item.year != 0).then_some(u32::from(self.item.year))
+    }
+
+    fn state(&self) -> &EntryState {
+        self.state
+    }
 }
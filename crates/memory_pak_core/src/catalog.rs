@@ -3,9 +3,11 @@ use std::sync::OnceLock;
 
 use crate::compiled::{
     CompiledCatalog, CompiledCollectible, CompiledCollection, CompiledConsole, CompiledGame,
+    CompiledReleases,
 };
 use crate::ids::EntryId;
-use crate::model::{Catalog, Collectible, Collection, Console, Game};
+use crate::model::{Catalog, Collectible, Collection, Console, Game, Releases};
+use crate::search_index::SearchIndex;
 
 static COMPILED_CATALOG_BYTES: &[u8] =
     include_bytes!(concat!(env!("OUT_DIR"), "/catalog.postcard"));
@@ -20,30 +22,52 @@ fn build_catalog() -> Catalog {
     let compiled: CompiledCatalog =
         postcard::from_bytes(COMPILED_CATALOG_BYTES).expect("compiled catalog should deserialize");
 
-    let consoles = compiled.consoles.into_iter().map(into_console).collect();
+    let consoles: Vec<Console> = compiled.consoles.into_iter().map(into_console).collect();
+    let console_index = consoles
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.id.clone(), i))
+        .collect();
 
     let mut games: HashMap<EntryId, Game> = HashMap::with_capacity(compiled.games.len());
+    let mut game_order = Vec::with_capacity(compiled.games.len());
     for game in compiled.games {
         let game = into_game(game);
+        game_order.push((game.id.clone(), game.console_id.clone()));
         games.insert(game.id.clone(), game);
     }
+    let search = SearchIndex::new(game_order, compiled.search);
+    let aliases = compiled
+        .aliases
+        .into_iter()
+        .map(|a| (EntryId::from_raw(a.from), EntryId::from_raw(a.to)))
+        .collect();
 
     let collections = compiled
         .collections
         .into_iter()
         .map(into_collection)
         .collect();
-    let collectibles = compiled
+    let collectibles: Vec<Collectible> = compiled
         .collectibles
         .into_iter()
         .map(into_collectible)
         .collect();
+    let collectible_index = collectibles
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.id.clone(), i))
+        .collect();
 
     Catalog {
         consoles,
         games,
         collections,
         collectibles,
+        console_index,
+        collectible_index,
+        search,
+        aliases,
     }
 }
 
@@ -69,6 +93,18 @@ fn into_game(g: CompiledGame) -> Game {
         developer: g.developer,
         publisher: g.publisher,
         year: g.year,
+        releases: into_releases(g.releases),
+    }
+}
+
+fn into_releases(r: CompiledReleases) -> Releases {
+    Releases {
+        jp: r.jp,
+        na: r.na,
+        pal: r.pal,
+        eu: r.eu,
+        au: r.au,
+        br: r.br,
     }
 }
 
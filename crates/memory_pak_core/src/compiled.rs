@@ -11,6 +11,8 @@ pub struct CompiledCatalog {
     pub games: Vec<CompiledGame>,
     pub collections: Vec<CompiledCollection>,
     pub collectibles: Vec<CompiledCollectible>,
+    pub search: CompiledSearchIndex,
+    pub aliases: Vec<CompiledAlias>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +36,18 @@ pub struct CompiledGame {
     pub developer: String,
     pub publisher: String,
     pub year: u32,
+    pub releases: CompiledReleases,
+}
+
+/// Regional release dates as ISO `YYYY`, `YYYY-MM` or `YYYY-MM-DD` strings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompiledReleases {
+    pub jp: Option<String>,
+    pub na: Option<String>,
+    pub pal: Option<String>,
+    pub eu: Option<String>,
+    pub au: Option<String>,
+    pub br: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,3 +68,27 @@ pub struct CompiledCollectible {
     pub variant: String,
     pub year: u8,
 }
+
+/// A retired entry id and the live id it now resolves to. Chains are
+/// flattened at build time, so `to` is always a catalog entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompiledAlias {
+    pub from: String,
+    pub to: String,
+}
+
+/// Inverted index from normalized title, publisher and developer words to
+/// positions in `CompiledCatalog::games`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompiledSearchIndex {
+    /// Sorted by word.
+    pub words: Vec<CompiledPosting>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompiledPosting {
+    pub word: String,
+    /// Ascending game positions, stored as gaps from the previous position so
+    /// most fit in a single varint byte.
+    pub game_gaps: Vec<u32>,
+}
@@ -0,0 +1,604 @@
+//! Spreadsheet-friendly CSV export and import.
+//!
+//! Export writes one row per tracked entry joined with catalog metadata.
+//! Import reads any CSV whose headers can be mapped onto entry fields and
+//! matches rows by id or, failing that, by title and console name.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::ids::{normalize_for_search, EntryId};
+use crate::model::{
+    Catalog, CatalogEntry, CollectionStats, EntryState, PersistedState, PlayStatus,
+};
+
+const EXPORT_HEADERS: [&str; 14] = [
+    "id",
+    "kind",
+    "title",
+    "console",
+    "year",
+    "publisher",
+    "owned",
+    "copies",
+    "favorite",
+    "wishlist",
+    "play_status",
+    "completed_on",
+    "tags",
+    "notes",
+];
+
+/// Header names to read each field from. Matching ignores case and
+/// surrounding whitespace; a field whose column is missing from the file is
+/// left untouched on matched entries.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CsvColumns {
+    pub id: String,
+    pub title: String,
+    pub console: String,
+    pub owned: String,
+    pub favorite: String,
+    pub wishlist: String,
+    pub notes: String,
+    pub play_status: String,
+    pub completed_on: String,
+    pub tags: String,
+}
+
+impl Default for CsvColumns {
+    fn default() -> Self {
+        Self {
+            id: "id".to_string(),
+            title: "title".to_string(),
+            console: "console".to_string(),
+            owned: "owned".to_string(),
+            favorite: "favorite".to_string(),
+            wishlist: "wishlist".to_string(),
+            notes: "notes".to_string(),
+            play_status: "play_status".to_string(),
+            completed_on: "completed_on".to_string(),
+            tags: "tags".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CsvImportOptions {
+    pub columns: CsvColumns,
+    /// Match rows without a known id by title plus console (or collection)
+    /// name. Consoles match on their name with an empty console cell.
+    pub match_title_console: bool,
+}
+
+impl Default for CsvImportOptions {
+    fn default() -> Self {
+        Self {
+            columns: CsvColumns::default(),
+            match_title_console: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub stats: CollectionStats,
+    /// Rows applied to an entry. Only counted for CSV imports.
+    pub matched: usize,
+    /// Rows that were skipped. Only reported for CSV imports.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unmatched: Vec<UnmatchedRow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmatchedRow {
+    /// 1-based line the row starts on.
+    pub line: usize,
+    /// The id, or title and console, the row was looked up by.
+    pub key: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("line {line}: {message}")]
+pub struct CsvError {
+    pub line: usize,
+    pub message: String,
+}
+
+pub(crate) fn export_csv_from_state(catalog: &Catalog, state: &PersistedState) -> String {
+    let mut entries: Vec<(&EntryId, &EntryState)> = state
+        .entries
+        .iter()
+        .filter(|(_, state)| !state.is_empty())
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut out = String::new();
+    write_record(&mut out, EXPORT_HEADERS);
+    for (id, entry) in entries {
+        let (title, console, year, publisher) = match catalog.get(id) {
+            Some(CatalogEntry::Console(c)) => (c.name.clone(), String::new(), 0, String::new()),
+            Some(CatalogEntry::Game(g)) => (
+                g.title.clone(),
+                catalog
+                    .console(&g.console_id)
+                    .map(|c| c.name.clone())
+                    .unwrap_or_else(|| g.console_short_id.clone()),
+                g.year,
+                g.publisher.clone(),
+            ),
+            Some(CatalogEntry::Collectible(c)) => (
+                c.name.clone(),
+                collection_name(catalog, &c.collection_id),
+                0,
+                String::new(),
+            ),
+            None => Default::default(),
+        };
+        write_record(
+            &mut out,
+            [
+                id.as_str().to_string(),
+                id.kind()
+                    .map(|k| k.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                title,
+                console,
+                if year == 0 {
+                    String::new()
+                } else {
+                    year.to_string()
+                },
+                publisher,
+                entry.owned.to_string(),
+                entry.copies.len().to_string(),
+                entry.favorite.to_string(),
+                entry.wishlist.to_string(),
+                entry
+                    .play_status
+                    .map(|s| play_status_name(s).to_string())
+                    .unwrap_or_default(),
+                entry
+                    .completed_on
+                    .map(|d| d.to_string())
+                    .unwrap_or_default(),
+                entry.tags.join("; "),
+                entry.notes.clone(),
+            ],
+        );
+    }
+    out
+}
+
+/// Applies every row that matches an entry and returns how many did, plus
+/// the rows that were skipped. `state` is left for the caller to normalize.
+pub(crate) fn apply_csv_import(
+    catalog: &Catalog,
+    state: &mut PersistedState,
+    csv: &str,
+    options: &CsvImportOptions,
+) -> Result<(usize, Vec<UnmatchedRow>), CsvError> {
+    let mut records = parse_records(csv)?.into_iter();
+    let Some((_, headers)) = records.next() else {
+        return Ok((0, Vec::new()));
+    };
+    let columns = ColumnIndexes::resolve(&headers, &options.columns);
+    if columns.id.is_none() && (columns.title.is_none() || !options.match_title_console) {
+        return Err(CsvError {
+            line: 1,
+            message: format!(
+                "no {:?} column to match rows by{}",
+                options.columns.id,
+                if options.match_title_console {
+                    format!(" and no {:?} column", options.columns.title)
+                } else {
+                    String::new()
+                }
+            ),
+        });
+    }
+    let titles = options
+        .match_title_console
+        .then(|| TitleLookup::new(catalog));
+
+    let mut matched = 0;
+    let mut unmatched = Vec::new();
+    for (line, record) in records {
+        if record.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        let raw = |index: Option<usize>| {
+            index.map(|i| record.get(i).map(String::as_str).unwrap_or_default())
+        };
+        let cell = |index: Option<usize>| raw(index).map(str::trim);
+
+        let id = cell(columns.id).filter(|id| !id.is_empty());
+        let live = id.and_then(|id| catalog.resolve(&EntryId::from_raw(id)).cloned());
+        let lookup = match (id, live, &titles) {
+            (_, Some(live), _) => Ok(live),
+            (id, None, Some(titles)) if cell(columns.title).is_some_and(|t| !t.is_empty()) => {
+                let title = cell(columns.title).unwrap_or_default();
+                let console = cell(columns.console).unwrap_or_default();
+                titles.find(title, console).map_err(|reason| {
+                    let key = if console.is_empty() {
+                        title.to_string()
+                    } else {
+                        format!("{title} ({console})")
+                    };
+                    let reason = match id {
+                        Some(id) => format!("unknown id {id:?}; {reason}"),
+                        None => reason,
+                    };
+                    (key, reason)
+                })
+            }
+            (Some(id), None, _) => Err((id.to_string(), "unknown id".to_string())),
+            (None, None, _) => Err((String::new(), "no id or title".to_string())),
+        };
+        let id = match lookup {
+            Ok(id) => id,
+            Err((key, reason)) => {
+                unmatched.push(UnmatchedRow { line, key, reason });
+                continue;
+            }
+        };
+
+        match RowValues::parse(&raw, &columns) {
+            Ok(values) => {
+                values.apply(state.entries.entry(id).or_default());
+                matched += 1;
+            }
+            Err(reason) => unmatched.push(UnmatchedRow {
+                line,
+                key: id.into_string(),
+                reason,
+            }),
+        }
+    }
+    Ok((matched, unmatched))
+}
+
+fn collection_name(catalog: &Catalog, collection_id: &str) -> String {
+    catalog
+        .collections
+        .iter()
+        .find(|c| c.id == collection_id)
+        .map(|c| c.name.clone())
+        .unwrap_or_else(|| collection_id.to_string())
+}
+
+fn play_status_name(status: PlayStatus) -> &'static str {
+    match status {
+        PlayStatus::Playing => "playing",
+        PlayStatus::Backlog => "backlog",
+        PlayStatus::Beaten => "beaten",
+        PlayStatus::Completed => "completed",
+        PlayStatus::Abandoned => "abandoned",
+    }
+}
+
+#[derive(Default)]
+struct ColumnIndexes {
+    id: Option<usize>,
+    title: Option<usize>,
+    console: Option<usize>,
+    owned: Option<usize>,
+    favorite: Option<usize>,
+    wishlist: Option<usize>,
+    notes: Option<usize>,
+    play_status: Option<usize>,
+    completed_on: Option<usize>,
+    tags: Option<usize>,
+}
+
+impl ColumnIndexes {
+    fn resolve(headers: &[String], names: &CsvColumns) -> Self {
+        let find = |name: &str| {
+            let name = name.trim();
+            (!name.is_empty())
+                .then(|| {
+                    headers
+                        .iter()
+                        .position(|h| h.trim().eq_ignore_ascii_case(name))
+                })
+                .flatten()
+        };
+        Self {
+            id: find(&names.id),
+            title: find(&names.title),
+            console: find(&names.console),
+            owned: find(&names.owned),
+            favorite: find(&names.favorite),
+            wishlist: find(&names.wishlist),
+            notes: find(&names.notes),
+            play_status: find(&names.play_status),
+            completed_on: find(&names.completed_on),
+            tags: find(&names.tags),
+        }
+    }
+}
+
+/// The mapped cells of one row, parsed. `None` means the column is absent.
+struct RowValues {
+    owned: Option<bool>,
+    favorite: Option<bool>,
+    wishlist: Option<bool>,
+    notes: Option<String>,
+    play_status: Option<Option<PlayStatus>>,
+    completed_on: Option<Option<NaiveDate>>,
+    tags: Option<Vec<String>>,
+}
+
+impl RowValues {
+    /// `raw` gives cells as written. Notes keep their whitespace, which the
+    /// writer quotes to preserve; every other value is trimmed.
+    fn parse<'a>(
+        raw: &impl Fn(Option<usize>) -> Option<&'a str>,
+        columns: &ColumnIndexes,
+    ) -> Result<Self, String> {
+        let cell = |index| raw(index).map(str::trim);
+        let flag = |index, name| cell(index).map(|value| parse_flag(value, name)).transpose();
+        Ok(Self {
+            owned: flag(columns.owned, "owned")?,
+            favorite: flag(columns.favorite, "favorite")?,
+            wishlist: flag(columns.wishlist, "wishlist")?,
+            notes: raw(columns.notes).map(str::to_string),
+            play_status: cell(columns.play_status)
+                .map(parse_play_status)
+                .transpose()?,
+            completed_on: cell(columns.completed_on)
+                .map(|value| {
+                    if value.is_empty() {
+                        return Ok(None);
+                    }
+                    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                        .map(Some)
+                        .map_err(|_| format!("invalid completed_on date {value:?}"))
+                })
+                .transpose()?,
+            tags: cell(columns.tags).map(|value| {
+                let mut tags: Vec<String> = Vec::new();
+                for tag in value.split(';').map(str::trim).filter(|t| !t.is_empty()) {
+                    if !tags.iter().any(|t| t == tag) {
+                        tags.push(tag.to_string());
+                    }
+                }
+                tags
+            }),
+        })
+    }
+
+    fn apply(self, entry: &mut EntryState) {
+        if let Some(owned) = self.owned {
+            entry.owned = owned;
+            if !owned {
+                entry.copies.clear();
+            }
+        }
+        if let Some(favorite) = self.favorite {
+            entry.favorite = favorite;
+        }
+        if let Some(wishlist) = self.wishlist {
+            entry.wishlist = wishlist;
+        }
+        if let Some(notes) = self.notes {
+            entry.notes = notes;
+        }
+        if let Some(play_status) = self.play_status {
+            entry.play_status = play_status;
+        }
+        if let Some(completed_on) = self.completed_on {
+            entry.completed_on = completed_on;
+        }
+        if entry.play_status.is_none() {
+            entry.completed_on = None;
+        }
+        if let Some(tags) = self.tags {
+            entry.tags = tags;
+        }
+    }
+}
+
+/// Blank cells read as `false` so spreadsheets can leave them empty.
+fn parse_flag(value: &str, name: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "" | "false" | "no" | "n" | "0" => Ok(false),
+        "true" | "yes" | "y" | "1" | "x" => Ok(true),
+        _ => Err(format!("invalid {name} value {value:?}")),
+    }
+}
+
+fn parse_play_status(value: &str) -> Result<Option<PlayStatus>, String> {
+    Ok(Some(match value.to_ascii_lowercase().as_str() {
+        "" => return Ok(None),
+        "playing" => PlayStatus::Playing,
+        "backlog" => PlayStatus::Backlog,
+        "beaten" => PlayStatus::Beaten,
+        "completed" => PlayStatus::Completed,
+        "abandoned" => PlayStatus::Abandoned,
+        _ => return Err(format!("invalid play_status {value:?}")),
+    }))
+}
+
+/// Entries keyed by normalized title and console or collection name.
+struct TitleLookup {
+    entries: HashMap<(String, String), Vec<EntryId>>,
+    /// Console short ids, abbreviations and collection ids mapped to the
+    /// normalized name used in `entries`.
+    aliases: HashMap<String, String>,
+}
+
+impl TitleLookup {
+    fn new(catalog: &Catalog) -> Self {
+        let mut entries: HashMap<(String, String), Vec<EntryId>> = HashMap::new();
+        let mut aliases = HashMap::new();
+        let mut console_names = HashMap::new();
+
+        for console in &catalog.consoles {
+            let name = normalize_for_search(&console.name);
+            for alias in [&console.short_id, &console.abbreviation] {
+                if !alias.is_empty() {
+                    aliases.insert(normalize_for_search(alias), name.clone());
+                }
+            }
+            console_names.insert(&console.id, name.clone());
+            entries
+                .entry((name, String::new()))
+                .or_default()
+                .push(console.id.clone());
+        }
+        for game in catalog.games.values() {
+            let console = console_names
+                .get(&game.console_id)
+                .cloned()
+                .unwrap_or_else(|| normalize_for_search(&game.console_short_id));
+            entries
+                .entry((normalize_for_search(&game.title), console))
+                .or_default()
+                .push(game.id.clone());
+        }
+        for collection in &catalog.collections {
+            aliases.insert(
+                normalize_for_search(&collection.id),
+                normalize_for_search(&collection.name),
+            );
+        }
+        for item in &catalog.collectibles {
+            let collection = normalize_for_search(&collection_name(catalog, &item.collection_id));
+            entries
+                .entry((normalize_for_search(&item.name), collection))
+                .or_default()
+                .push(item.id.clone());
+        }
+
+        Self { entries, aliases }
+    }
+
+    fn find(&self, title: &str, console: &str) -> Result<EntryId, String> {
+        let console = normalize_for_search(console);
+        let console = self.aliases.get(&console).cloned().unwrap_or(console);
+        match self
+            .entries
+            .get(&(normalize_for_search(title), console))
+            .map(Vec::as_slice)
+        {
+            Some([id]) => Ok(id.clone()),
+            Some(ids) => Err(format!("ambiguous title; {} entries match", ids.len())),
+            None => Err("no entry with this title and console".to_string()),
+        }
+    }
+}
+
+fn write_record<S: AsRef<str>>(out: &mut String, fields: impl IntoIterator<Item = S>) {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let field = field.as_ref();
+        if field.contains([',', '"', '\n', '\r']) || field.trim() != field {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push('\n');
+}
+
+/// Splits RFC 4180 CSV into records, each tagged with the line it starts on.
+fn parse_records(input: &str) -> Result<Vec<(usize, Vec<String>)>, CsvError> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut quoted = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                record.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(CsvError {
+            line: record_line,
+            message: "unterminated quoted field".to_string(),
+        });
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_handle_quotes_newlines_and_crlf() {
+        let records = parse_records("a,b\r\n\"x, \"\"y\"\"\",\"two\nlines\"\nlast,\n").unwrap();
+        assert_eq!(
+            records,
+            vec![
+                (1, vec!["a".to_string(), "b".to_string()]),
+                (2, vec!["x, \"y\"".to_string(), "two\nlines".to_string()]),
+                (4, vec!["last".to_string(), String::new()]),
+            ]
+        );
+        assert_eq!(parse_records("a,\"b").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn written_records_parse_back() {
+        let fields = [
+            "plain",
+            "with, comma",
+            "say \"hi\"",
+            " padded",
+            "multi\nline",
+        ];
+        let mut out = String::new();
+        write_record(&mut out, fields);
+        let records = parse_records(&out).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].1, fields);
+    }
+}
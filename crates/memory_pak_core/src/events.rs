@@ -0,0 +1,113 @@
+//! Change notifications, so every window or view over one collection can
+//! update without re-querying after each command.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ids::EntryId;
+use crate::model::{CollectionStats, EntryList, EntryState, PersistedState};
+
+/// What one state-changing call did. Sent once per call, after it succeeds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeEvent {
+    /// Entries whose saved state changed, sorted.
+    pub ids: Vec<EntryId>,
+    /// Lists that were created, changed or deleted, sorted. A renamed list
+    /// appears under its old and new names.
+    pub lists: Vec<String>,
+    pub stats: CollectionStats,
+}
+
+pub type ChangeListener = Box<dyn Fn(&ChangeEvent) + Send + Sync>;
+
+/// Handle returned by [`crate::MemoryPakApp::subscribe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(u64);
+
+#[derive(Default)]
+pub(crate) struct Listeners {
+    next: u64,
+    listeners: Vec<(ListenerId, ChangeListener)>,
+}
+
+impl fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Listeners")
+            .field("count", &self.listeners.len())
+            .finish()
+    }
+}
+
+impl Listeners {
+    pub(crate) fn add(&mut self, listener: ChangeListener) -> ListenerId {
+        let id = ListenerId(self.next);
+        self.next += 1;
+        self.listeners.push((id, listener));
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: ListenerId) -> bool {
+        let before = self.listeners.len();
+        self.listeners.retain(|(listener, _)| *listener != id);
+        self.listeners.len() != before
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.listeners.is_empty()
+    }
+
+    pub(crate) fn emit(&self, event: &ChangeEvent) {
+        for (_, listener) in &self.listeners {
+            listener(event);
+        }
+    }
+}
+
+/// Ids whose state differs between `before` and `after`, sorted.
+pub(crate) fn changed_ids(
+    before: &HashMap<EntryId, EntryState>,
+    after: &HashMap<EntryId, EntryState>,
+) -> Vec<EntryId> {
+    let mut ids: BTreeSet<&EntryId> = BTreeSet::new();
+    for (id, state) in after {
+        if before.get(id) != Some(state) {
+            ids.insert(id);
+        }
+    }
+    ids.extend(before.keys().filter(|id| !after.contains_key(*id)));
+    ids.into_iter().cloned().collect()
+}
+
+/// Names of lists that differ between `before` and `after`, sorted.
+pub(crate) fn changed_lists(before: &[EntryList], after: &[EntryList]) -> Vec<String> {
+    fn members<'a>(lists: &'a [EntryList], name: &str) -> Option<&'a [EntryId]> {
+        lists
+            .iter()
+            .find(|list| list.name == name)
+            .map(|list| list.entries.as_slice())
+    }
+    let names: BTreeSet<&str> = before
+        .iter()
+        .chain(after)
+        .map(|list| list.name.as_str())
+        .collect();
+    names
+        .into_iter()
+        .filter(|name| members(before, name) != members(after, name))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Everything that differs between two states.
+pub(crate) fn diff_states(
+    before: &PersistedState,
+    after: &PersistedState,
+) -> (Vec<EntryId>, Vec<String>) {
+    (
+        changed_ids(&before.entries, &after.entries),
+        changed_lists(&before.lists, &after.lists),
+    )
+}
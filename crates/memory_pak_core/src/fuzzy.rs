@@ -0,0 +1,159 @@
+//! Typo-tolerant text scoring for search clauses.
+//!
+//! Both sides are expected to be `normalize_for_search` output already.
+
+/// Longest word, in characters, that is compared with edit distance. Longer
+/// words still match exactly or as substrings.
+const MAX_FUZZY_WORD: usize = 32;
+
+/// Scores `needle` against `haystack`, or `None` when it does not match.
+///
+/// Exact matches beat prefixes, prefixes beat infixes, and infixes beat typo
+/// matches, which are only tried when `typos` is set. `primary` fields
+/// (titles and names) score double.
+pub(crate) fn text_score(haystack: &str, needle: &str, primary: bool, typos: bool) -> Option<u32> {
+    let score = exact_score(haystack, needle)
+        .or_else(|| typos.then(|| typo_score(haystack, needle)).flatten())?;
+    Some(if primary { score * 2 } else { score })
+}
+
+fn exact_score(haystack: &str, needle: &str) -> Option<u32> {
+    if haystack == needle {
+        return Some(50);
+    }
+    let mut words = haystack.split_whitespace();
+    if words.clone().any(|word| word == needle) {
+        return Some(40);
+    }
+    if words.any(|word| word.starts_with(needle)) {
+        return Some(30);
+    }
+    haystack.contains(needle).then_some(20)
+}
+
+/// Matches a single-word needle against each word of `haystack`, or against
+/// a same-length prefix of it so partially typed words still match.
+fn typo_score(haystack: &str, needle: &str) -> Option<u32> {
+    let mut needle_buf = ['\0'; MAX_FUZZY_WORD];
+    let needle = to_chars(needle, &mut needle_buf)?;
+    let max = allowed_edits(needle.len());
+    if max == 0 || needle.contains(&' ') {
+        return None;
+    }
+
+    let mut word_buf = ['\0'; MAX_FUZZY_WORD];
+    let mut best: Option<usize> = None;
+    for word in haystack.split_whitespace() {
+        let Some(word) = to_chars(word, &mut word_buf) else {
+            continue;
+        };
+        let prefix = &word[..word.len().min(needle.len())];
+        let distance = [
+            bounded_osa(word, needle, max),
+            bounded_osa(prefix, needle, max),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        best = match (best, distance) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        if best == Some(1) {
+            break;
+        }
+    }
+    best.map(|distance| if distance <= 1 { 10 } else { 5 })
+}
+
+/// Edits tolerated for a word of `len` characters.
+fn allowed_edits(len: usize) -> usize {
+    match len {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+fn to_chars<'a>(value: &str, buf: &'a mut [char; MAX_FUZZY_WORD]) -> Option<&'a [char]> {
+    let mut len = 0;
+    for c in value.chars() {
+        *buf.get_mut(len)? = c;
+        len += 1;
+    }
+    Some(&buf[..len])
+}
+
+/// Optimal string alignment distance (Levenshtein plus adjacent
+/// transpositions), or `None` once it is known to exceed `max`.
+fn bounded_osa(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let n = b.len();
+    let mut before = [0usize; MAX_FUZZY_WORD + 1];
+    let mut prev = [0usize; MAX_FUZZY_WORD + 1];
+    let mut cur = [0usize; MAX_FUZZY_WORD + 1];
+    for (j, slot) in prev.iter_mut().enumerate().take(n + 1) {
+        *slot = j;
+    }
+
+    for i in 1..=a.len() {
+        cur[0] = i;
+        let mut row_min = i;
+        for j in 1..=n {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut value = (prev[j] + 1).min(cur[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                value = value.min(before[j - 2] + 1);
+            }
+            cur[j] = value;
+            row_min = row_min.min(value);
+        }
+        if row_min > max {
+            return None;
+        }
+        before = prev;
+        prev = cur;
+    }
+
+    (prev[n] <= max).then_some(prev[n])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn osa(a: &str, b: &str, max: usize) -> Option<usize> {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        bounded_osa(&a, &b, max)
+    }
+
+    #[test]
+    fn distance_counts_transpositions_as_one_edit() {
+        assert_eq!(osa("ocarina", "ocarnia", 2), Some(1));
+        assert_eq!(osa("castlevania", "castelvania", 2), Some(1));
+        assert_eq!(osa("kitten", "sitting", 3), Some(3));
+        assert_eq!(osa("kitten", "sitting", 2), None);
+    }
+
+    #[test]
+    fn exact_matches_outrank_typos() {
+        let exact = text_score("the legend of zelda ocarina of time", "ocarina", true, true);
+        let typo = text_score("the legend of zelda ocarina of time", "ocarnia", true, true);
+        assert!(exact > typo);
+        assert!(typo.is_some());
+        assert_eq!(
+            text_score("castlevania", "castelvania", false, true),
+            Some(10)
+        );
+        assert_eq!(text_score("castlevania", "castelv", false, true), Some(10));
+        assert_eq!(text_score("mario", "zzz", true, true), None);
+        assert_eq!(
+            text_score("fez", "fes", true, true),
+            None,
+            "short words need exact matches"
+        );
+    }
+}
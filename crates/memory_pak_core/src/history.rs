@@ -0,0 +1,86 @@
+//! Undo and redo. Each step stores what it overwrote, so undoing it is
+//! putting those values back, and the values it replaces become the redo.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::BatchItemResult;
+use crate::ids::EntryId;
+use crate::model::{CollectionStats, EntryList, EntryState};
+
+/// Undo steps kept; the oldest is dropped past this.
+pub const HISTORY_LIMIT: usize = 100;
+
+/// What an undo or redo changed, and whether more steps are available.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryResult {
+    /// Restored entries, sorted by id. An entry that was removed has the
+    /// default state.
+    pub items: Vec<BatchItemResult>,
+    /// Lists that were restored.
+    pub lists: Vec<String>,
+    pub stats: CollectionStats,
+    pub can_undo: bool,
+    pub can_redo: bool,
+}
+
+/// Values to put back. `None` for an entry means it did not exist; `None`
+/// for `lists` means the lists were not touched.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Step {
+    pub(crate) entries: Vec<(EntryId, Option<EntryState>)>,
+    pub(crate) lists: Option<Vec<EntryList>>,
+}
+
+impl Step {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.lists.is_none()
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    undo: VecDeque<Step>,
+    redo: Vec<Step>,
+}
+
+impl History {
+    /// Records a new change. Anything undone before it can no longer be
+    /// redone. A step that changed nothing is dropped.
+    pub(crate) fn push_change(&mut self, step: Step) {
+        if step.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        self.push_undo(step);
+    }
+
+    pub(crate) fn push_undo(&mut self, step: Step) {
+        if self.undo.len() == HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(step);
+    }
+
+    pub(crate) fn push_redo(&mut self, step: Step) {
+        self.redo.push(step);
+    }
+
+    pub(crate) fn pop_undo(&mut self) -> Option<Step> {
+        self.undo.pop_back()
+    }
+
+    pub(crate) fn pop_redo(&mut self) -> Option<Step> {
+        self.redo.pop()
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
@@ -1,7 +1,8 @@
 use std::fmt;
 
 use serde::{Deserialize, Serialize};
-use unicode_normalization::UnicodeNormalization;
+
+pub use crate::normalize::normalize_for_search;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -36,7 +37,7 @@ impl EntryKind {
 /// - `console:nes`
 /// - `game:nes/super-mario-bros`
 /// - `collectible:legodimensions/batman`
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct EntryId(String);
 
@@ -85,25 +86,6 @@ impl AsRef<str> for EntryId {
     }
 }
 
-/// Lowercase, Unicode-folded, punctuation-stripped text suitable for substring search.
-pub fn normalize_for_search(value: &str) -> String {
-    value
-        .nfd()
-        .filter(|c| !is_combining_mark(*c))
-        .filter(|c| !matches!(*c, '\'' | ':' | '-' | '_' | '.' | ',' | '!' | '?' | ';'))
-        .flat_map(|c| c.to_lowercase())
-        .collect()
-}
-
-fn is_combining_mark(c: char) -> bool {
-    let code = c as u32;
-    (0x0300..=0x036F).contains(&code)
-        || (0x1AB0..=0x1AFF).contains(&code)
-        || (0x1DC0..=0x1DFF).contains(&code)
-        || (0x20D0..=0x20FF).contains(&code)
-        || (0xFE20..=0xFE2F).contains(&code)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
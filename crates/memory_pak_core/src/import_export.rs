@@ -1,9 +1,19 @@
+use std::collections::HashSet;
+
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
+use crate::aliases::IdRewrite;
 use crate::ids::EntryId;
-use crate::model::{EntryState, PersistedState};
+use crate::model::{
+    Catalog, CollectionStats, EntryList, EntryState, OwnedCopy, PersistedState, PlayStatus,
+    SpendingReport,
+};
+use crate::spending::spending_report;
 
-pub const EXPORT_VERSION: &str = "2.0";
+/// `2.1` added play status. `2.0` files import unchanged.
+pub const EXPORT_VERSION: &str = "2.1";
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -17,6 +27,14 @@ pub struct ExportEntry {
     pub wishlist: bool,
     #[serde(default)]
     pub notes: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub copies: Vec<OwnedCopy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub play_status: Option<PlayStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_on: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -25,9 +43,18 @@ pub struct ExportData {
     pub version: String,
     pub exported_at: String,
     pub entries: Vec<ExportEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lists: Vec<EntryList>,
+    /// Purchase totals at export time. Informational: imports recompute them
+    /// from the copies in `entries`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub totals: Option<SpendingReport>,
 }
 
-pub fn export_json_from_state(state: &PersistedState) -> Result<String, serde_json::Error> {
+pub fn export_json_from_state(
+    catalog: &Catalog,
+    state: &PersistedState,
+) -> Result<String, serde_json::Error> {
     let mut entries: Vec<ExportEntry> = state
         .entries
         .iter()
@@ -38,6 +65,10 @@ pub fn export_json_from_state(state: &PersistedState) -> Result<String, serde_js
             favorite: state.favorite,
             wishlist: state.wishlist,
             notes: state.notes.clone(),
+            copies: state.copies.clone(),
+            play_status: state.play_status,
+            completed_on: state.completed_on,
+            tags: state.tags.clone(),
         })
         .collect();
 
@@ -47,21 +78,308 @@ pub fn export_json_from_state(state: &PersistedState) -> Result<String, serde_js
         version: EXPORT_VERSION.to_string(),
         exported_at: chrono::Utc::now().to_rfc3339(),
         entries,
+        lists: state.lists.clone(),
+        totals: Some(spending_report(catalog, state)),
     };
 
     serde_json::to_string_pretty(&export)
 }
 
-pub fn apply_import(state: &mut PersistedState, import: ExportData) {
-    for entry in import.entries {
-        state.entries.insert(
-            entry.id,
-            EntryState {
-                owned: entry.owned,
-                favorite: entry.favorite,
-                wishlist: entry.wishlist,
-                notes: entry.notes,
-            },
+/// How an imported entry combines with an entry already tracked locally.
+/// Entries only present on one side are taken as they are.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportStrategy {
+    /// The imported entry replaces the local one.
+    #[default]
+    Overwrite,
+    /// Local entries win; only entries new to this device are imported.
+    KeepLocal,
+    /// Flags are OR-ed and tags unioned; copies, notes and play status are
+    /// taken from the import only where the local entry has none.
+    MergeFlags,
+    /// Like `Overwrite`, but differing notes are joined instead of replaced.
+    ConcatNotes,
+}
+
+/// Whether an import is layered over the local state or replaces it.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportMode {
+    /// Entries and lists in the file are combined with local ones; anything
+    /// the file does not mention is kept.
+    #[default]
+    Merge,
+    /// The file becomes the whole state: entries and lists it does not
+    /// mention are removed. The strategy is ignored.
+    Replace,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImportOptions {
+    pub mode: ImportMode,
+    pub strategy: ImportStrategy,
+}
+
+/// Entry counts an applied import changed, plus the resulting stats.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub stats: CollectionStats,
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    /// Retired ids in the file that were moved to their current id.
+    pub renamed: Vec<IdRewrite>,
+    /// Ids in the file that match no catalog entry. They are stored as-is.
+    pub unresolved: Vec<EntryId>,
+}
+
+/// What applying an import would do, computed without touching state.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPreview {
+    /// Entries not tracked locally yet.
+    pub added: Vec<EntryId>,
+    pub changed: Vec<EntryChange>,
+    /// Tracked entries the import would reset to empty, or drop because a
+    /// replace-mode file does not mention them.
+    pub cleared: Vec<EntryId>,
+    /// Ids missing from the catalog. They are stored as-is on apply.
+    pub unknown: Vec<EntryId>,
+    /// Retired ids the import will store under their current id.
+    pub renamed: Vec<IdRewrite>,
+    pub unchanged: usize,
+    /// Lists the file would create.
+    pub added_lists: Vec<String>,
+    /// Local lists whose members the import would change.
+    pub changed_lists: Vec<String>,
+    /// Local lists a replace-mode file drops by not having them.
+    pub removed_lists: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryChange {
+    pub id: EntryId,
+    pub fields: Vec<FieldChange>,
+}
+
+/// One field of an entry before and after the import, in its JSON form.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+pub fn preview_import(
+    catalog: &Catalog,
+    state: &PersistedState,
+    import: &ExportData,
+    options: ImportOptions,
+) -> ImportPreview {
+    let mut preview = ImportPreview::default();
+    let empty = EntryState::default();
+    let strategy = match options.mode {
+        ImportMode::Merge => options.strategy,
+        ImportMode::Replace => ImportStrategy::Overwrite,
+    };
+
+    for entry in &import.entries {
+        if catalog.get(&entry.id).is_none() {
+            preview.unknown.push(entry.id.clone());
+            continue;
+        }
+        let local = state.entries.get(&entry.id).unwrap_or(&empty);
+        let merged = merge_entry(local, imported_state(entry.clone()), strategy);
+        if merged == *local {
+            preview.unchanged += 1;
+        } else if local.is_empty() {
+            preview.added.push(entry.id.clone());
+        } else if merged.is_empty() {
+            preview.cleared.push(entry.id.clone());
+        } else {
+            preview.changed.push(EntryChange {
+                id: entry.id.clone(),
+                fields: field_changes(local, &merged),
+            });
+        }
+    }
+    if options.mode == ImportMode::Replace {
+        let mentioned: HashSet<&EntryId> = import.entries.iter().map(|e| &e.id).collect();
+        preview.cleared.extend(
+            state
+                .entries
+                .iter()
+                .filter(|(id, local)| !local.is_empty() && !mentioned.contains(id))
+                .map(|(id, _)| id.clone()),
+        );
+        preview.removed_lists.extend(
+            state
+                .lists
+                .iter()
+                .filter(|local| !import.lists.iter().any(|list| list.name == local.name))
+                .map(|local| local.name.clone()),
         );
     }
+    for list in &import.lists {
+        match state.lists.iter().find(|local| local.name == list.name) {
+            None => preview.added_lists.push(list.name.clone()),
+            Some(local) => {
+                if merge_list(local, list.clone(), strategy).entries != local.entries {
+                    preview.changed_lists.push(list.name.clone());
+                }
+            }
+        }
+    }
+
+    preview.added.sort();
+    preview.changed.sort_by(|a, b| a.id.cmp(&b.id));
+    preview.cleared.sort();
+    preview.unknown.sort();
+    preview.added_lists.sort();
+    preview.changed_lists.sort();
+    preview.removed_lists.sort();
+    preview
+}
+
+/// Applies an import to `state`. In merge mode entries and same-named lists
+/// combine one by one using the strategy; in replace mode the file's
+/// entries and lists become the whole state.
+pub fn apply_import(state: &mut PersistedState, import: ExportData, options: ImportOptions) {
+    if options.mode == ImportMode::Replace {
+        state.entries = import
+            .entries
+            .into_iter()
+            .map(|entry| (entry.id.clone(), imported_state(entry)))
+            .collect();
+        state.lists = import.lists;
+        return;
+    }
+
+    let strategy = options.strategy;
+    for entry in import.entries {
+        let imported = imported_state(entry.clone());
+        let local = state.entries.entry(entry.id).or_default();
+        *local = merge_entry(local, imported, strategy);
+    }
+    for list in import.lists {
+        match state.lists.iter_mut().find(|l| l.name == list.name) {
+            Some(existing) => *existing = merge_list(existing, list, strategy),
+            None => state.lists.push(list),
+        }
+    }
+}
+
+/// A same-named list after an import. `KeepLocal` keeps the local members,
+/// `MergeFlags` appends imported members the local list lacks, and the
+/// other strategies take the imported members.
+fn merge_list(local: &EntryList, imported: EntryList, strategy: ImportStrategy) -> EntryList {
+    match strategy {
+        ImportStrategy::Overwrite | ImportStrategy::ConcatNotes => imported,
+        ImportStrategy::KeepLocal => local.clone(),
+        ImportStrategy::MergeFlags => {
+            let mut merged = local.clone();
+            for id in imported.entries {
+                if !merged.entries.contains(&id) {
+                    merged.entries.push(id);
+                }
+            }
+            merged
+        }
+    }
+}
+
+fn imported_state(entry: ExportEntry) -> EntryState {
+    let mut state = EntryState {
+        owned: entry.owned,
+        favorite: entry.favorite,
+        wishlist: entry.wishlist,
+        notes: entry.notes,
+        copies: entry.copies,
+        play_status: entry.play_status,
+        completed_on: entry.completed_on,
+        tags: entry.tags,
+    };
+    state.normalize_copies();
+    state
+}
+
+pub(crate) fn merge_entry(
+    local: &EntryState,
+    imported: EntryState,
+    strategy: ImportStrategy,
+) -> EntryState {
+    if local.is_empty() {
+        return imported;
+    }
+    match strategy {
+        ImportStrategy::Overwrite => imported,
+        ImportStrategy::KeepLocal => local.clone(),
+        ImportStrategy::MergeFlags => {
+            let mut merged = local.clone();
+            merged.favorite |= imported.favorite;
+            merged.wishlist |= imported.wishlist;
+            if merged.copies.is_empty() {
+                merged.copies = imported.copies;
+            }
+            if merged.notes.is_empty() {
+                merged.notes = imported.notes;
+            }
+            if merged.play_status.is_none() {
+                merged.play_status = imported.play_status;
+                merged.completed_on = imported.completed_on;
+            }
+            for tag in imported.tags {
+                if !merged.tags.contains(&tag) {
+                    merged.tags.push(tag);
+                }
+            }
+            merged.normalize_copies();
+            merged
+        }
+        ImportStrategy::ConcatNotes => {
+            let notes = concat_notes(&local.notes, &imported.notes);
+            EntryState { notes, ..imported }
+        }
+    }
+}
+
+fn concat_notes(local: &str, imported: &str) -> String {
+    if imported.is_empty() || local.contains(imported) {
+        local.to_string()
+    } else if local.is_empty() || imported.contains(local) {
+        imported.to_string()
+    } else {
+        format!("{local}\n\n{imported}")
+    }
+}
+
+fn field_changes(old: &EntryState, new: &EntryState) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    let mut diff = |field: &str, old: Value, new: Value| {
+        if old != new {
+            changes.push(FieldChange {
+                field: field.to_string(),
+                old,
+                new,
+            });
+        }
+    };
+    diff("owned", json!(old.owned), json!(new.owned));
+    diff("favorite", json!(old.favorite), json!(new.favorite));
+    diff("wishlist", json!(old.wishlist), json!(new.wishlist));
+    diff("notes", json!(old.notes), json!(new.notes));
+    diff("copies", json!(old.copies), json!(new.copies));
+    diff("playStatus", json!(old.play_status), json!(new.play_status));
+    diff(
+        "completedOn",
+        json!(old.completed_on),
+        json!(new.completed_on),
+    );
+    diff("tags", json!(old.tags), json!(new.tags));
+    changes
 }
@@ -1,21 +1,66 @@
+mod activity;
+mod aliases;
 mod app;
 mod catalog;
 mod compiled;
+mod csv;
+mod events;
+mod fuzzy;
+mod history;
 mod ids;
 mod import_export;
+mod migrate;
 mod model;
+mod normalize;
+mod orphans;
+#[cfg(feature = "persistence")]
+mod persistence;
 mod query;
+mod search_index;
+mod spending;
+mod timeline;
 
-pub use app::{CoreError, MemoryPakApp, SetItemNotesInput, SetItemStatusInput};
+pub use activity::{
+    ActivityChange, ActivityEvent, ActivityLog, ActivityPolicy, ActivityQuery, ActivityView,
+    DEFAULT_ACTIVITY_LIMIT,
+};
+pub use aliases::{IdReport, IdRewrite};
+pub use app::{
+    BatchChange, BatchItemResult, BatchResult, CoreError, MemoryPakApp, QueryPatchInput,
+    QueryPatchResult, RenameListInput, SetItemCopiesInput, SetItemNotesInput,
+    SetItemPlayStatusInput, SetItemStatusInput, SetItemTagsInput, SetListInput, StatusPatch,
+};
 pub use catalog::catalog;
+pub use csv::{CsvColumns, CsvError, CsvImportOptions, ImportReport, UnmatchedRow};
+pub use events::{ChangeEvent, ChangeListener, ListenerId};
+pub use history::{HistoryResult, HISTORY_LIMIT};
 pub use ids::{normalize_for_search, EntryId, EntryKind};
-pub use import_export::{apply_import, export_json_from_state, ExportData, ExportEntry};
+pub use import_export::{
+    apply_import, export_json_from_state, preview_import, EntryChange, ExportData, ExportEntry,
+    FieldChange, ImportMode, ImportOptions, ImportPreview, ImportStrategy, ImportSummary,
+    EXPORT_VERSION,
+};
+pub use migrate::{migrate_state, parse_persisted_state, StateError, STATE_VERSION};
 pub use model::{
-    Catalog, Collectible, CollectibleView, Collection, CollectionStats, CollectionView, Console,
-    ConsoleCounts, ConsoleView, EntryState, Game, GameView, InitialState, ItemKind, MutationResult,
-    PersistedState,
+    Catalog, CatalogEntry, Collectible, CollectibleView, Collection, CollectionStats,
+    CollectionView, Completeness, Condition, Console, ConsoleCounts, ConsoleView, EntryList,
+    EntryState, Game, GameView, InitialState, ItemKind, ItemView, MutationResult, OwnedCopy,
+    PersistedState, PlayStatus, Region, Releases, SpendGroup, SpendTotal, SpendingReport, TagCount,
+    YearSpend,
+};
+pub use orphans::{OrphanEntry, OrphanRepair, OrphanRepairSummary, OrphanSuggestion};
+#[cfg(feature = "persistence")]
+pub use persistence::{
+    default_state_path, load_persisted_state, load_persisted_state_from, save_persisted_state,
+    save_persisted_state_to,
+};
+pub use query::{
+    parse_search, FilterBy, QueryInput, QueryResult, QuerySyntaxError, SearchClause, SearchField,
+    SearchQuery, SearchTerm, SortKey, StatusFlag,
+};
+pub use timeline::{
+    TimeBucket, TimelineInput, TimelinePoint, TimelineReport, TimelineSeries, MAX_TIMELINE_BUCKETS,
 };
-pub use query::{FilterBy, QueryInput, QueryResult, SortKey};
 
 #[cfg(test)]
 mod tests {
@@ -82,7 +127,7 @@ mod tests {
         let parsed_a: ExportData = serde_json::from_str(&first).unwrap();
         let parsed_b: ExportData = serde_json::from_str(&second).unwrap();
         assert_eq!(parsed_a.entries, parsed_b.entries);
-        assert_eq!(parsed_a.version, "2.0");
+        assert_eq!(parsed_a.version, "2.1");
         assert_eq!(parsed_a.entries.len(), 1);
         assert_eq!(parsed_a.entries[0].id, mario.id);
     }
@@ -167,11 +212,18 @@ mod tests {
                 favorite: false,
                 wishlist: false,
                 notes: "cart only".to_string(),
+                copies: Vec::new(),
+                play_status: None,
+                completed_on: None,
+                tags: Vec::new(),
             }],
+            lists: Vec::new(),
+            totals: None,
         };
         let json = serde_json::to_string(&export).unwrap();
 
-        app.import_json(&json).expect("import");
+        app.import_json(&json, ImportOptions::default())
+            .expect("import");
         assert_eq!(
             app.persisted_state()
                 .entries
@@ -181,7 +233,7 @@ mod tests {
         );
 
         assert!(matches!(
-            app.import_json("{ nope"),
+            app.import_json("{ nope", ImportOptions::default()),
             Err(CoreError::InvalidImport(_))
         ));
     }
@@ -222,4 +274,1476 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn region_filter_and_release_sort_use_regional_dates() {
+        let app = MemoryPakApp::default();
+        let result = app.query_games(QueryInput {
+            console_id: Some("console:nes".to_string()),
+            region: Some(Region::Na),
+            sort_by: Some(SortKey::ReleaseDate),
+            ..Default::default()
+        });
+        assert!(result.total > 0);
+        let dates: Vec<&str> = result
+            .items
+            .iter()
+            .map(|g| g.releases.na.as_deref().expect("filtered to NA releases"))
+            .collect();
+        assert!(dates.windows(2).all(|w| w[0] <= w[1]));
+
+        let pal = app.query_games(QueryInput {
+            console_id: Some("console:nintendo3ds".to_string()),
+            region: Some(Region::Pal),
+            ..Default::default()
+        });
+        assert!(pal.total > 0, "PAL filter should include EU/AU-only lists");
+    }
+
+    #[test]
+    fn copies_drive_owned_and_copy_counts() {
+        let mut app = MemoryPakApp::default();
+        let game = app
+            .query_games(QueryInput {
+                search: Some("Mario".to_string()),
+                limit: Some(1),
+                ..Default::default()
+            })
+            .items
+            .remove(0);
+
+        let result = app
+            .set_item_copies(SetItemCopiesInput {
+                id: game.id.clone(),
+                copies: vec![
+                    OwnedCopy {
+                        completeness: Some(Completeness::Loose),
+                        ..Default::default()
+                    },
+                    OwnedCopy {
+                        completeness: Some(Completeness::Cib),
+                        region: Some(Region::Na),
+                        ..Default::default()
+                    },
+                ],
+            })
+            .expect("copies update");
+        assert!(result.state.owned);
+        assert_eq!(result.stats.owned_games, 1);
+        assert_eq!(result.stats.owned_game_copies, 2);
+        let console = app
+            .initial_state()
+            .consoles
+            .into_iter()
+            .find(|c| c.id == game.console_id)
+            .expect("console view");
+        assert_eq!(console.game_counts.owned, 1);
+        assert_eq!(console.game_counts.owned_copies, 2);
+
+        let result = app
+            .set_item_status(SetItemStatusInput {
+                id: game.id.clone(),
+                owned: Some(false),
+                favorite: None,
+                wishlist: None,
+            })
+            .expect("status update");
+        assert!(result.state.copies.is_empty());
+        assert_eq!(result.stats.owned_game_copies, 0);
+        assert!(!app.persisted_state().entries.contains_key(&game.id));
+    }
+
+    #[test]
+    fn legacy_owned_flag_becomes_a_default_copy() {
+        let game_id = catalog().games.keys().next().unwrap().clone();
+        let json = format!(r#"{{"entries":{{"{game_id}":{{"owned":true}}}}}}"#);
+        let state = parse_persisted_state(&json).unwrap();
+        let app = MemoryPakApp::from_persisted_state(state);
+        let entry = &app.persisted_state().entries[&game_id];
+        assert_eq!(entry.copies, vec![OwnedCopy::default()]);
+        assert_eq!(app.collection_stats().owned_game_copies, 1);
+    }
+
+    #[test]
+    fn spending_report_groups_by_currency_console_and_year() {
+        let mut app = MemoryPakApp::default();
+        let game = app
+            .query_games(QueryInput {
+                search: Some("Mario".to_string()),
+                limit: Some(1),
+                ..Default::default()
+            })
+            .items
+            .remove(0);
+        let bought = |price, currency: &str, year| OwnedCopy {
+            purchase_price: Some(price),
+            currency: Some(currency.to_string()),
+            acquired_on: chrono::NaiveDate::from_ymd_opt(year, 6, 1),
+            source: Some("eBay".to_string()),
+            ..Default::default()
+        };
+
+        app.set_item_copies(SetItemCopiesInput {
+            id: game.id.clone(),
+            copies: vec![
+                bought(1500, "USD", 2023),
+                bought(2500, " usd", 2024),
+                bought(3000, "EUR", 2024),
+                OwnedCopy::default(),
+            ],
+        })
+        .expect("copies update");
+
+        let report = app.spending_report();
+        let usd = report.totals.iter().find(|t| t.currency == "USD").unwrap();
+        assert_eq!((usd.amount, usd.copies), (4000, 2));
+        assert_eq!(report.totals.len(), 2);
+        assert_eq!(report.by_console.len(), 1);
+        assert_eq!(report.by_console[0].id, game.console_id.as_str());
+        let years: Vec<i32> = report.by_year.iter().map(|y| y.year).collect();
+        assert_eq!(years, vec![2023, 2024]);
+
+        let export: ExportData = serde_json::from_str(&app.export_json().unwrap()).unwrap();
+        assert_eq!(export.totals.as_ref(), Some(&report));
+        let mut restored = MemoryPakApp::default();
+        restored
+            .import_json(&app.export_json().unwrap(), ImportOptions::default())
+            .expect("import");
+        assert_eq!(restored.spending_report(), report);
+    }
+
+    #[test]
+    fn play_status_filters_sorts_and_round_trips() {
+        let mut app = MemoryPakApp::default();
+        let games = app
+            .query_games(QueryInput {
+                search: Some("Mario".to_string()),
+                limit: Some(3),
+                ..Default::default()
+            })
+            .items;
+        let completed_on = chrono::NaiveDate::from_ymd_opt(2024, 3, 9);
+        for (game, status) in games.iter().zip([
+            PlayStatus::Backlog,
+            PlayStatus::Completed,
+            PlayStatus::Playing,
+        ]) {
+            app.set_item_play_status(SetItemPlayStatusInput {
+                id: game.id.clone(),
+                play_status: Some(status),
+                completed_on,
+            })
+            .expect("play status update");
+        }
+
+        let backlog = app.query_games(QueryInput {
+            filter_by: Some(FilterBy::Backlog),
+            ..Default::default()
+        });
+        assert_eq!(backlog.total, 1);
+        assert_eq!(backlog.items[0].id, games[0].id);
+
+        let sorted = app.query_games(QueryInput {
+            sort_by: Some(SortKey::PlayStatus),
+            limit: Some(3),
+            ..Default::default()
+        });
+        let order: Vec<_> = sorted.items.iter().map(|g| g.state.play_status).collect();
+        assert_eq!(
+            order,
+            vec![
+                Some(PlayStatus::Playing),
+                Some(PlayStatus::Backlog),
+                Some(PlayStatus::Completed)
+            ]
+        );
+
+        let console = app
+            .initial_state()
+            .consoles
+            .into_iter()
+            .find(|c| c.id == games[0].console_id)
+            .unwrap();
+        assert_eq!(console.game_counts.backlog, 1);
+
+        let mut restored = MemoryPakApp::default();
+        restored
+            .import_json(&app.export_json().unwrap(), ImportOptions::default())
+            .expect("import");
+        assert_eq!(
+            restored.persisted_state().entries,
+            app.persisted_state().entries
+        );
+
+        let cleared = app
+            .set_item_play_status(SetItemPlayStatusInput {
+                id: games[1].id.clone(),
+                play_status: None,
+                completed_on,
+            })
+            .expect("clear play status");
+        assert_eq!(cleared.state.completed_on, None);
+        assert!(!app.persisted_state().entries.contains_key(&games[1].id));
+    }
+
+    #[test]
+    fn tags_filter_every_kind_and_feed_the_vocabulary() {
+        let mut app = MemoryPakApp::default();
+        let console = app.catalog().consoles[0].id.clone();
+        let game = app.catalog().games.keys().next().unwrap().clone();
+        let collectible = app.catalog().collectibles[0].id.clone();
+        for id in [&console, &game, &collectible] {
+            app.set_item_tags(SetItemTagsInput {
+                id: id.clone(),
+                tags: vec![
+                    " to sell ".to_string(),
+                    "to sell".to_string(),
+                    String::new(),
+                ],
+            })
+            .expect("tag update");
+        }
+        app.set_item_tags(SetItemTagsInput {
+            id: game.clone(),
+            tags: vec!["to sell".to_string(), "lent out".to_string()],
+        })
+        .expect("tag update");
+
+        let tagged = |tag: &str| QueryInput {
+            tags: vec![tag.to_string()],
+            ..Default::default()
+        };
+        assert_eq!(app.query_consoles(tagged("to sell")).items[0].id, console);
+        assert_eq!(app.query_games(tagged("to sell")).items[0].id, game);
+        assert_eq!(app.query_collectibles(tagged("to sell")).total, 1);
+        assert_eq!(app.query_games(tagged("lent out")).total, 1);
+        assert_eq!(app.query_consoles(tagged("lent out")).total, 0);
+
+        let tags = app.initial_state().tags;
+        assert_eq!(
+            tags,
+            vec![
+                TagCount {
+                    tag: "lent out".to_string(),
+                    count: 1
+                },
+                TagCount {
+                    tag: "to sell".to_string(),
+                    count: 3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_keep_order_filter_queries_and_round_trip() {
+        let mut app = MemoryPakApp::default();
+        let games: Vec<EntryId> = app
+            .query_games(QueryInput {
+                limit: Some(3),
+                ..Default::default()
+            })
+            .items
+            .into_iter()
+            .map(|g| g.id)
+            .collect();
+        let collectible = app.catalog().collectibles[0].id.clone();
+
+        let members = vec![games[2].clone(), collectible.clone(), games[0].clone()];
+        let list = app
+            .set_list(SetListInput {
+                name: "Christmas 2025 haul".to_string(),
+                entries: members.clone(),
+            })
+            .expect("create list");
+        assert_eq!(list.entries, members);
+
+        let in_list = app.query_games(QueryInput {
+            list: Some("Christmas 2025 haul".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(in_list.total, 2);
+        assert!(app
+            .query_games(QueryInput {
+                list: Some("missing".to_string()),
+                ..Default::default()
+            })
+            .items
+            .is_empty());
+
+        assert!(matches!(
+            app.set_list(SetListInput {
+                name: "bad".to_string(),
+                entries: vec![EntryId::from_raw("game:nope/nope")],
+            }),
+            Err(CoreError::UnknownEntry(_))
+        ));
+
+        app.rename_list(RenameListInput {
+            name: "Christmas 2025 haul".to_string(),
+            new_name: "Gifts".to_string(),
+        })
+        .expect("rename");
+
+        let mut restored = MemoryPakApp::default();
+        restored
+            .import_json(&app.export_json().unwrap(), ImportOptions::default())
+            .expect("import");
+        assert_eq!(
+            restored.persisted_state().lists,
+            app.persisted_state().lists
+        );
+
+        app.delete_list("Gifts").expect("delete");
+        assert!(matches!(
+            app.delete_list("Gifts"),
+            Err(CoreError::UnknownList(_))
+        ));
+    }
+
+    #[test]
+    fn structured_search_combines_fields_ranges_and_flags() {
+        let mut app = MemoryPakApp::default();
+        let query = |app: &MemoryPakApp, search: &str| {
+            app.query_games(QueryInput {
+                search: Some(search.to_string()),
+                ..Default::default()
+            })
+        };
+
+        let capcom = query(&app, "publisher:capcom year:1990..1995 console:snes");
+        assert!(capcom.error.is_none());
+        assert!(capcom.total > 0);
+        for game in &capcom.items {
+            assert_eq!(game.console_id.as_str(), "console:snes");
+            assert!((1990..=1995).contains(&game.year));
+            assert!(normalize_for_search(&game.publisher).contains("capcom"));
+        }
+
+        let owned_id = capcom.items[0].id.clone();
+        app.set_item_status(SetItemStatusInput {
+            id: owned_id.clone(),
+            owned: Some(true),
+            favorite: None,
+            wishlist: None,
+        })
+        .expect("status update");
+        let not_owned = query(&app, "publisher:capcom year:1990..1995 console:snes -owned");
+        assert_eq!(not_owned.total, capcom.total - 1);
+        assert!(not_owned.items.iter().all(|g| g.id != owned_id));
+
+        let phrase = query(&app, "\"super mario\"");
+        assert!(phrase
+            .items
+            .iter()
+            .all(|g| normalize_for_search(&g.title).contains("super mario")
+                || normalize_for_search(&g.console_name).contains("super mario")));
+
+        let broken = query(&app, "publisher:\"capcom");
+        assert_eq!(broken.total, 0);
+        assert_eq!(
+            broken.error.map(|e| (e.start, e.end)),
+            Some((10, 17)),
+            "error points at the unterminated quote"
+        );
+
+        let consoles = app.query_consoles(QueryInput {
+            search: Some("console:snes".to_string()),
+            ..Default::default()
+        });
+        assert!(consoles
+            .items
+            .iter()
+            .any(|c| c.id.as_str() == "console:snes"));
+        let collectibles = app.query_collectibles(QueryInput {
+            search: Some("collection:skylanders group:giants".to_string()),
+            ..Default::default()
+        });
+        assert!(collectibles.total > 0);
+        assert!(collectibles.items.iter().all(|c| c.group == "Giants"));
+    }
+
+    #[test]
+    fn fuzzy_search_tolerates_typos_and_ranks_by_relevance() {
+        let app = MemoryPakApp::default();
+        let query = |search: &str| {
+            app.query_games(QueryInput {
+                search: Some(search.to_string()),
+                ..Default::default()
+            })
+        };
+
+        let zelda = query("zelda ocarnia");
+        assert!(zelda
+            .items
+            .iter()
+            .any(|g| normalize_for_search(&g.title).contains("ocarina of time")));
+
+        let castlevania = query("castelvania");
+        assert!(castlevania.total > 0);
+        assert!(castlevania
+            .items
+            .iter()
+            .all(|g| normalize_for_search(&g.title).contains("castlevania")));
+
+        let exact = query("castlevania");
+        assert_eq!(exact.total, castlevania.total);
+        assert_eq!(
+            normalize_for_search(&exact.items[0].title),
+            "castlevania",
+            "an exact title match ranks first by default"
+        );
+
+        let by_title = app.query_games(QueryInput {
+            search: Some("castlevania".to_string()),
+            sort_by: Some(SortKey::Title),
+            ..Default::default()
+        });
+        assert_eq!(by_title.total, exact.total);
+
+        assert_eq!(query("console:snse").total, 0, "field values match exactly");
+    }
+
+    #[test]
+    fn running_stats_match_a_full_recount() {
+        let mut app = MemoryPakApp::default();
+        let games = app
+            .query_games(QueryInput {
+                search: Some("console:nes".to_string()),
+                limit: Some(3),
+                ..Default::default()
+            })
+            .items;
+        let console = games[0].console_id.clone();
+        let recount = |app: &MemoryPakApp| {
+            let fresh = MemoryPakApp::from_persisted_state(app.persisted_state().clone());
+            let counts = |app: &MemoryPakApp| {
+                app.initial_state()
+                    .consoles
+                    .into_iter()
+                    .find(|c| c.id == console)
+                    .map(|c| c.game_counts)
+            };
+            assert_eq!(app.collection_stats(), fresh.collection_stats());
+            assert_eq!(counts(app), counts(&fresh));
+        };
+
+        for game in &games {
+            app.set_item_status(SetItemStatusInput {
+                id: game.id.clone(),
+                owned: Some(true),
+                favorite: Some(true),
+                wishlist: None,
+            })
+            .expect("status update");
+            recount(&app);
+        }
+        app.set_item_copies(SetItemCopiesInput {
+            id: games[0].id.clone(),
+            copies: vec![OwnedCopy::default(), OwnedCopy::default()],
+        })
+        .expect("copies update");
+        app.set_item_play_status(SetItemPlayStatusInput {
+            id: games[1].id.clone(),
+            play_status: Some(PlayStatus::Backlog),
+            completed_on: None,
+        })
+        .expect("play status update");
+        app.set_item_status(SetItemStatusInput {
+            id: console.clone(),
+            owned: Some(true),
+            favorite: None,
+            wishlist: Some(true),
+        })
+        .expect("console update");
+        recount(&app);
+
+        for game in &games {
+            let result = app
+                .set_item_status(SetItemStatusInput {
+                    id: game.id.clone(),
+                    owned: Some(false),
+                    favorite: Some(false),
+                    wishlist: None,
+                })
+                .expect("status update");
+            assert_eq!(result.stats, app.collection_stats());
+            recount(&app);
+        }
+        let stats = app.collection_stats();
+        assert_eq!(stats.owned_games, 0);
+        assert_eq!(stats.owned_game_copies, 0);
+        assert_eq!(stats.owned_consoles, 1);
+    }
+
+    #[test]
+    fn get_item_returns_the_typed_view_for_every_kind() {
+        let mut app = MemoryPakApp::default();
+        let catalog = app.catalog();
+        let console = &catalog.consoles[0];
+        let game = catalog.games.values().next().expect("a game");
+        let collectible = &catalog.collectibles[0];
+
+        assert!(
+            matches!(catalog.get(&console.id), Some(CatalogEntry::Console(c)) if c.id == console.id)
+        );
+        assert!(matches!(catalog.get(&game.id), Some(CatalogEntry::Game(g)) if g.id == game.id));
+        assert!(matches!(
+            catalog.get(&collectible.id),
+            Some(CatalogEntry::Collectible(c)) if c.id == collectible.id
+        ));
+        assert!(catalog.get(&EntryId::from_raw("console:missing")).is_none());
+        assert!(catalog.get(&EntryId::from_raw("nonsense")).is_none());
+
+        let (console_id, game_id, collectible_id) =
+            (console.id.clone(), game.id.clone(), collectible.id.clone());
+        app.set_item_status(SetItemStatusInput {
+            id: game_id.clone(),
+            owned: Some(true),
+            favorite: None,
+            wishlist: None,
+        })
+        .expect("status update");
+
+        match app.get_item(&game_id).expect("game view") {
+            ItemView::Game(view) => {
+                assert_eq!(view.id, game_id);
+                assert!(view.state.owned);
+                assert!(!view.console_name.is_empty());
+            }
+            other => panic!("expected a game view, got {other:?}"),
+        }
+        assert!(matches!(
+            app.get_item(&console_id),
+            Ok(ItemView::Console(_))
+        ));
+        match app.get_item(&collectible_id).expect("collectible view") {
+            ItemView::Collectible(view) => assert!(!view.collection_name.is_empty()),
+            other => panic!("expected a collectible view, got {other:?}"),
+        }
+        assert!(matches!(
+            app.get_item(&EntryId::from_raw("game:nes/does-not-exist")),
+            Err(CoreError::UnknownEntry(_))
+        ));
+
+        let json = serde_json::to_value(app.get_item(&game_id).unwrap()).unwrap();
+        assert_eq!(json["kind"], "game");
+    }
+
+    #[test]
+    fn csv_round_trips_and_matches_by_title_and_console() {
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let mut app = MemoryPakApp::default();
+        app.set_item_status(SetItemStatusInput {
+            id: mario.clone(),
+            owned: Some(true),
+            favorite: Some(true),
+            wishlist: None,
+        })
+        .expect("status update");
+        app.set_item_notes(SetItemNotesInput {
+            id: mario.clone(),
+            notes: "  cart, \"boxed\"\nsecond line ".to_string(),
+        })
+        .expect("notes update");
+        app.set_item_play_status(SetItemPlayStatusInput {
+            id: mario.clone(),
+            play_status: Some(PlayStatus::Completed),
+            completed_on: chrono::NaiveDate::from_ymd_opt(2023, 7, 4),
+        })
+        .expect("play status update");
+        app.set_item_tags(SetItemTagsInput {
+            id: mario.clone(),
+            tags: vec!["childhood".to_string(), "platformer".to_string()],
+        })
+        .expect("tags update");
+
+        let csv = app.export_csv();
+        assert!(csv.starts_with("id,kind,title,console,year,publisher,owned,"));
+        assert!(csv.contains("Super Mario Bros."));
+
+        let mut restored = MemoryPakApp::default();
+        let report = restored
+            .import_csv(&csv, CsvImportOptions::default())
+            .expect("csv import");
+        assert_eq!(report.matched, 1);
+        assert!(report.unmatched.is_empty());
+        assert_eq!(
+            restored.persisted_state().entries.get(&mario),
+            app.persisted_state().entries.get(&mario)
+        );
+
+        let sheet = "Game;System;Have\n\
+                     Super Mario Bros.;NES;yes\n\
+                     Not A Real Game;NES;yes\n\
+                     Super Mario Bros.;NES;maybe\n"
+            .replace(';', ",");
+        let mut fresh = MemoryPakApp::default();
+        let report = fresh
+            .import_csv(
+                &sheet,
+                CsvImportOptions {
+                    columns: CsvColumns {
+                        title: "game".to_string(),
+                        console: "system".to_string(),
+                        owned: "have".to_string(),
+                        ..Default::default()
+                    },
+                    match_title_console: true,
+                },
+            )
+            .expect("mapped csv import");
+        assert_eq!(report.matched, 1);
+        assert_eq!(report.stats.owned_games, 1);
+        assert!(fresh.persisted_state().entries[&mario].owned);
+        assert_eq!(
+            report
+                .unmatched
+                .iter()
+                .map(|row| row.line)
+                .collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+
+        let err = fresh
+            .import_csv(
+                "Game,Have\nSuper Mario Bros.,yes\n",
+                CsvImportOptions {
+                    match_title_console: false,
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+        assert!(matches!(err, CoreError::InvalidCsv(_)));
+    }
+
+    #[test]
+    fn import_preview_reports_changes_and_strategies_resolve_conflicts() {
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let zelda = EntryId::from_raw("game:nes/the-legend-of-zelda");
+        let metroid = EntryId::from_raw("game:nes/metroid");
+        let ghost = EntryId::from_raw("game:nes/not-in-the-catalog");
+
+        let mut local = MemoryPakApp::default();
+        local
+            .set_item_status(SetItemStatusInput {
+                id: mario.clone(),
+                owned: Some(true),
+                favorite: None,
+                wishlist: None,
+            })
+            .expect("status update");
+        local
+            .set_item_notes(SetItemNotesInput {
+                id: mario.clone(),
+                notes: "local note".to_string(),
+            })
+            .expect("notes update");
+        local
+            .set_item_status(SetItemStatusInput {
+                id: zelda.clone(),
+                owned: None,
+                favorite: Some(true),
+                wishlist: None,
+            })
+            .expect("status update");
+
+        let entry = |id: &EntryId, wishlist: bool, notes: &str| ExportEntry {
+            id: id.clone(),
+            owned: false,
+            favorite: false,
+            wishlist,
+            notes: notes.to_string(),
+            copies: Vec::new(),
+            play_status: None,
+            completed_on: None,
+            tags: Vec::new(),
+        };
+        let backup = serde_json::to_string(&ExportData {
+            version: EXPORT_VERSION.to_string(),
+            exported_at: "2024-01-01T00:00:00Z".to_string(),
+            entries: vec![
+                entry(&mario, true, "backup note"),
+                entry(&zelda, false, ""),
+                entry(&metroid, true, ""),
+                entry(&ghost, true, ""),
+            ],
+            lists: Vec::new(),
+            totals: None,
+        })
+        .unwrap();
+
+        let before = local.persisted_state().clone();
+        let preview = local
+            .preview_import_json(&backup, ImportOptions::default())
+            .expect("preview");
+        assert_eq!(local.persisted_state(), &before, "preview applies nothing");
+        assert_eq!(preview.added, vec![metroid.clone()]);
+        assert_eq!(preview.cleared, vec![zelda.clone()]);
+        assert_eq!(preview.unknown, vec![ghost.clone()]);
+        assert_eq!(preview.changed.len(), 1);
+        let fields: Vec<&str> = preview.changed[0]
+            .fields
+            .iter()
+            .map(|f| f.field.as_str())
+            .collect();
+        assert_eq!(fields, ["owned", "wishlist", "notes", "copies"]);
+        let notes = &preview.changed[0].fields[2];
+        assert_eq!(
+            (notes.old.as_str(), notes.new.as_str()),
+            (Some("local note"), Some("backup note"))
+        );
+
+        let keep = local
+            .preview_import_json(
+                &backup,
+                ImportOptions {
+                    strategy: ImportStrategy::KeepLocal,
+                    ..Default::default()
+                },
+            )
+            .expect("preview");
+        assert!(keep.changed.is_empty() && keep.cleared.is_empty());
+        assert_eq!(keep.unchanged, 2);
+
+        let apply = |strategy| {
+            let mut app = MemoryPakApp::from_persisted_state(before.clone());
+            let options = ImportOptions {
+                strategy,
+                ..Default::default()
+            };
+            app.import_json(&backup, options).expect("import");
+            app.persisted_state().clone()
+        };
+        let merged = apply(ImportStrategy::MergeFlags);
+        assert!(merged.entries[&mario].owned);
+        assert!(merged.entries[&mario].wishlist);
+        assert_eq!(merged.entries[&mario].notes, "local note");
+        assert!(merged.entries[&zelda].favorite);
+        assert!(merged.entries[&metroid].wishlist);
+
+        let concat = apply(ImportStrategy::ConcatNotes);
+        assert_eq!(concat.entries[&mario].notes, "local note\n\nbackup note");
+        assert!(!concat.entries[&mario].owned);
+
+        let overwritten = apply(ImportStrategy::Overwrite);
+        assert_eq!(overwritten.entries[&mario].notes, "backup note");
+        assert!(!overwritten.entries.contains_key(&zelda));
+    }
+
+    #[test]
+    fn replace_mode_restores_exactly_the_backup() {
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let zelda = EntryId::from_raw("game:nes/the-legend-of-zelda");
+        let metroid = EntryId::from_raw("game:nes/metroid");
+        let own = |app: &mut MemoryPakApp, id: &EntryId| {
+            app.set_item_status(SetItemStatusInput {
+                id: id.clone(),
+                owned: Some(true),
+                favorite: None,
+                wishlist: None,
+            })
+            .expect("status update");
+        };
+
+        let mut device = MemoryPakApp::default();
+        own(&mut device, &mario);
+        own(&mut device, &zelda);
+        device
+            .set_list(SetListInput {
+                name: "to sell".to_string(),
+                entries: vec![zelda.clone()],
+            })
+            .expect("list");
+        let backup = device.export_json().unwrap();
+
+        // The device diverges after the backup was taken.
+        own(&mut device, &metroid);
+        device
+            .set_item_notes(SetItemNotesInput {
+                id: mario.clone(),
+                notes: "newer note".to_string(),
+            })
+            .expect("notes update");
+        device.delete_list("to sell").expect("delete list");
+
+        let replace = ImportOptions {
+            mode: ImportMode::Replace,
+            ..Default::default()
+        };
+        let preview = device
+            .preview_import_json(&backup, replace)
+            .expect("preview");
+        assert_eq!(preview.cleared, vec![metroid.clone()]);
+        assert_eq!(preview.changed.len(), 1);
+
+        let merged = MemoryPakApp::from_persisted_state(device.persisted_state().clone())
+            .import_json(&backup, ImportOptions::default())
+            .expect("merge import");
+        assert_eq!((merged.added, merged.updated, merged.removed), (0, 1, 0));
+
+        let summary = device
+            .import_json(&backup, replace)
+            .expect("replace import");
+        assert_eq!((summary.added, summary.updated, summary.removed), (0, 1, 1));
+        assert_eq!(summary.stats.owned_games, 2);
+        assert!(!device.persisted_state().entries.contains_key(&metroid));
+        assert_eq!(device.persisted_state().entries[&mario].notes, "");
+        assert_eq!(device.persisted_state().lists.len(), 1);
+    }
+
+    #[test]
+    fn unknown_ids_are_kept_and_reported_on_load_and_import() {
+        let gone = EntryId::from_raw("game:nes/no-such-game");
+        let json = format!(r#"{{"version":1,"entries":{{"{gone}":{{"notes":"keep me"}}}}}}"#);
+        let app = MemoryPakApp::from_persisted_state(parse_persisted_state(&json).unwrap());
+        assert_eq!(app.id_report().unresolved, vec![gone.clone()]);
+        assert!(app.id_report().rewritten.is_empty());
+        assert_eq!(app.initial_state().id_report, *app.id_report());
+        assert_eq!(app.persisted_state().entries[&gone].notes, "keep me");
+
+        let mut fresh = MemoryPakApp::default();
+        let summary = fresh
+            .import_json(&app.export_json().unwrap(), ImportOptions::default())
+            .unwrap();
+        assert_eq!(summary.unresolved, vec![gone]);
+        assert!(summary.renamed.is_empty());
+    }
+
+    #[test]
+    fn orphans_are_listed_with_suggestions_and_can_be_repaired() {
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let typo = EntryId::from_raw("game:nes/super-mario-brso");
+        let gone = EntryId::from_raw("game:nes/no-such-game-at-all");
+        let json = format!(
+            r#"{{"version":1,
+                "entries":{{"{typo}":{{"owned":true,"notes":"boxed"}},"{gone}":{{"favorite":true}}}},
+                "lists":[{{"name":"Shelf","entries":["{typo}","{gone}"]}}]}}"#
+        );
+        let mut app = MemoryPakApp::from_persisted_state(parse_persisted_state(&json).unwrap());
+        assert_eq!(app.collection_stats().owned_games, 0);
+        assert_eq!(app.collection_stats().favorite_games, 0);
+
+        let orphans = app.orphans();
+        assert_eq!(
+            orphans.iter().map(|o| &o.id).collect::<Vec<_>>(),
+            vec![&gone, &typo]
+        );
+        let typo_orphan = &orphans[1];
+        assert_eq!(typo_orphan.state.notes, "boxed");
+        assert_eq!(typo_orphan.lists, vec!["Shelf".to_string()]);
+        assert_eq!(typo_orphan.suggestions[0].id, mario);
+
+        let err = app
+            .repair_orphans(vec![
+                OrphanRepair::Discard { id: gone.clone() },
+                OrphanRepair::Discard { id: mario.clone() },
+            ])
+            .unwrap_err();
+        assert!(matches!(err, CoreError::NotOrphaned(_)));
+        assert_eq!(app.orphans().len(), 2);
+
+        let summary = app
+            .repair_orphans(vec![
+                OrphanRepair::Remap {
+                    id: typo.clone(),
+                    to: mario.clone(),
+                },
+                OrphanRepair::Discard { id: gone.clone() },
+            ])
+            .unwrap();
+        assert_eq!((summary.remapped, summary.discarded), (1, 1));
+        assert_eq!(summary.stats.owned_games, 1);
+        assert!(app.orphans().is_empty());
+        assert_eq!(app.persisted_state().entries[&mario].notes, "boxed");
+        assert_eq!(app.persisted_state().lists[0].entries, vec![mario]);
+    }
+
+    #[test]
+    fn listeners_receive_one_event_per_successful_change() {
+        use std::sync::{Arc, Mutex};
+
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let snes = EntryId::from_raw("console:snes");
+        let events = Arc::new(Mutex::new(Vec::<ChangeEvent>::new()));
+        let mut app = MemoryPakApp::default();
+        let sink = Arc::clone(&events);
+        let listener = app.subscribe(move |event| sink.lock().unwrap().push(event.clone()));
+
+        app.set_item_status(SetItemStatusInput {
+            id: mario.clone(),
+            owned: Some(true),
+            favorite: None,
+            wishlist: None,
+        })
+        .unwrap();
+        app.set_item_notes(SetItemNotesInput {
+            id: EntryId::from_raw("game:nes/no-such-game"),
+            notes: "x".to_string(),
+        })
+        .unwrap_err();
+        app.set_list(SetListInput {
+            name: "Shelf".to_string(),
+            entries: vec![mario.clone()],
+        })
+        .unwrap();
+        let json = format!(
+            r#"{{"version":"2.1","exportedAt":"","entries":[{{"id":"{snes}","wishlist":true}}]}}"#
+        );
+        app.import_json(&json, ImportOptions::default()).unwrap();
+        // Neither a no-op change nor an unchanged policy is announced.
+        app.set_item_status(SetItemStatusInput {
+            id: mario.clone(),
+            owned: Some(true),
+            favorite: None,
+            wishlist: None,
+        })
+        .unwrap();
+        let policy = Some(ActivityPolicy {
+            max_events: Some(10),
+            max_age_days: None,
+        });
+        app.set_activity_policy(policy);
+        app.set_activity_policy(policy);
+
+        {
+            let events = events.lock().unwrap();
+            assert_eq!(events.len(), 4);
+            assert_eq!(events[0].ids, vec![mario.clone()]);
+            assert_eq!(events[0].stats.owned_games, 1);
+            assert_eq!(events[1].lists, vec!["Shelf".to_string()]);
+            assert_eq!(events[2].ids, vec![snes.clone()]);
+            assert_eq!(events[2].stats.wishlist_consoles, 1);
+            assert!(events[3].ids.is_empty() && events[3].lists.is_empty());
+        }
+
+        assert!(app.unsubscribe(listener));
+        app.delete_list("Shelf").unwrap();
+        assert_eq!(events.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn reloading_state_announces_the_difference_and_clears_history() {
+        use std::sync::{Arc, Mutex};
+
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let zelda = EntryId::from_raw("game:nes/the-legend-of-zelda");
+        let events = Arc::new(Mutex::new(Vec::<ChangeEvent>::new()));
+        let mut app = MemoryPakApp::default();
+        let sink = Arc::clone(&events);
+        app.subscribe(move |event| sink.lock().unwrap().push(event.clone()));
+        app.set_item_status(SetItemStatusInput {
+            id: mario.clone(),
+            owned: Some(true),
+            favorite: None,
+            wishlist: None,
+        })
+        .unwrap();
+
+        let mut other = MemoryPakApp::default();
+        other
+            .set_item_status(SetItemStatusInput {
+                id: zelda.clone(),
+                owned: None,
+                favorite: None,
+                wishlist: Some(true),
+            })
+            .unwrap();
+        let saved = other.persisted_state().clone();
+        app.reload_state(saved.clone());
+        app.reload_state(saved);
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].ids, vec![mario, zelda.clone()]);
+        assert_eq!(events[1].stats.owned_games, 0);
+        assert_eq!(events[1].stats.wishlist_games, 1);
+        assert!(!app.can_undo());
+        assert!(app.persisted_state().entries[&zelda].wishlist);
+    }
+
+    #[test]
+    fn batches_apply_all_changes_or_none() {
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let zelda = EntryId::from_raw("game:nes/the-legend-of-zelda");
+        let mut app = MemoryPakApp::default();
+        let status = |id: &EntryId, owned: bool| {
+            BatchChange::Status(SetItemStatusInput {
+                id: id.clone(),
+                owned: Some(owned),
+                favorite: None,
+                wishlist: None,
+            })
+        };
+
+        let err = app
+            .apply_batch(vec![
+                status(&mario, true),
+                status(&EntryId::from_raw("game:nes/no-such-game"), true),
+            ])
+            .unwrap_err();
+        assert!(matches!(err, CoreError::UnknownEntry(_)));
+        assert!(app.persisted_state().entries.is_empty());
+
+        let mut changes: Vec<BatchChange> = serde_json::from_str(&format!(
+            r#"[{{"op":"status","id":"{zelda}","owned":true}},
+                {{"op":"notes","id":"{zelda}","notes":"gold cart"}}]"#
+        ))
+        .unwrap();
+        changes.insert(0, status(&mario, true));
+        let result = app.apply_batch(changes).unwrap();
+
+        assert_eq!(result.items.len(), 3);
+        assert_eq!(result.items[1].id, zelda);
+        assert!(result.items[1].state.notes.is_empty());
+        assert_eq!(result.items[2].state.notes, "gold cart");
+        assert_eq!(result.stats.owned_games, 2);
+        assert_eq!(app.collection_stats(), result.stats);
+    }
+
+    #[test]
+    fn status_patches_cover_every_query_match_and_support_dry_runs() {
+        let mut app = MemoryPakApp::default();
+        let query = QueryInput {
+            search: Some("console:nes publisher:nintendo".to_string()),
+            limit: Some(1),
+            ..QueryInput::default()
+        };
+        let matched = app.query_games(query.clone()).total;
+        assert!(matched > 1);
+        let input = |dry_run| QueryPatchInput {
+            kind: ItemKind::Game,
+            query: query.clone(),
+            patch: StatusPatch {
+                favorite: Some(true),
+                ..StatusPatch::default()
+            },
+            dry_run,
+        };
+
+        let preview = app.apply_status_to_query(input(true)).unwrap();
+        assert_eq!((preview.matched, preview.affected), (matched, matched));
+        assert_eq!(preview.stats.favorite_games, 0);
+        assert!(app.persisted_state().entries.is_empty());
+
+        let applied = app.apply_status_to_query(input(false)).unwrap();
+        assert_eq!(applied.affected, matched);
+        assert_eq!(applied.stats.favorite_games, matched);
+
+        let again = app.apply_status_to_query(input(false)).unwrap();
+        assert_eq!((again.matched, again.affected), (matched, 0));
+
+        let mut bad = input(true);
+        bad.query.search = Some("year:abc".to_string());
+        assert!(matches!(
+            app.apply_status_to_query(bad),
+            Err(CoreError::InvalidQuery(_))
+        ));
+    }
+
+    #[test]
+    fn undo_and_redo_walk_back_through_status_notes_imports_and_batches() {
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let snes = EntryId::from_raw("console:snes");
+        let mut app = MemoryPakApp::default();
+        assert!(app.undo().is_none());
+
+        app.set_item_status(SetItemStatusInput {
+            id: mario.clone(),
+            owned: Some(true),
+            favorite: None,
+            wishlist: None,
+        })
+        .unwrap();
+        app.set_item_notes(SetItemNotesInput {
+            id: mario.clone(),
+            notes: "boxed".to_string(),
+        })
+        .unwrap();
+        app.set_item_notes(SetItemNotesInput {
+            id: mario.clone(),
+            notes: String::new(),
+        })
+        .unwrap();
+
+        let undone = app.undo().unwrap();
+        assert_eq!(undone.items[0].state.notes, "boxed");
+        assert!(undone.can_undo && undone.can_redo);
+        let redone = app.redo().unwrap();
+        assert!(redone.items[0].state.notes.is_empty());
+        assert!(!redone.can_redo);
+
+        let json = format!(
+            r#"{{"version":"2.1","exportedAt":"","entries":[{{"id":"{snes}","wishlist":true}}]}}"#
+        );
+        app.import_json(&json, ImportOptions::default()).unwrap();
+        app.apply_batch(vec![BatchChange::Status(SetItemStatusInput {
+            id: snes.clone(),
+            owned: Some(true),
+            favorite: None,
+            wishlist: None,
+        })])
+        .unwrap();
+        assert_eq!(app.collection_stats().owned_consoles, 1);
+
+        app.undo().unwrap();
+        let undone = app.undo().unwrap();
+        assert_eq!(undone.items[0].id, snes);
+        assert_eq!(undone.items[0].state, EntryState::default());
+        assert_eq!(undone.stats.wishlist_consoles, 0);
+        assert_eq!(undone.stats.owned_consoles, 0);
+        assert!(!app.persisted_state().entries.contains_key(&snes));
+
+        // A new change drops what could have been redone.
+        app.set_item_status(SetItemStatusInput {
+            id: mario.clone(),
+            owned: None,
+            favorite: Some(true),
+            wishlist: None,
+        })
+        .unwrap();
+        assert!(!app.can_redo());
+
+        while app.undo().is_some() {}
+        assert!(app.persisted_state().entries.is_empty());
+        assert_eq!(app.collection_stats().owned_games, 0);
+    }
+
+    #[test]
+    fn unchanged_lists_leave_undo_and_redo_alone() {
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let shelf = || SetListInput {
+            name: "Shelf".to_string(),
+            entries: vec![mario.clone()],
+        };
+        let mut app = MemoryPakApp::default();
+        app.set_list(shelf()).unwrap();
+        app.set_list(SetListInput {
+            entries: Vec::new(),
+            ..shelf()
+        })
+        .unwrap();
+        app.undo().unwrap();
+        assert!(app.can_redo());
+
+        app.set_list(shelf()).unwrap();
+        app.rename_list(RenameListInput {
+            name: "Shelf".to_string(),
+            new_name: "Shelf".to_string(),
+        })
+        .unwrap();
+        assert!(app.can_redo());
+        app.undo().unwrap();
+        assert!(!app.can_undo());
+        assert!(app.persisted_state().lists.is_empty());
+    }
+
+    #[test]
+    fn undo_history_is_bounded() {
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let mut app = MemoryPakApp::default();
+        for n in 0..HISTORY_LIMIT + 5 {
+            app.set_item_notes(SetItemNotesInput {
+                id: mario.clone(),
+                notes: n.to_string(),
+            })
+            .unwrap();
+        }
+        let mut undone = 0;
+        while app.undo().is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, HISTORY_LIMIT);
+        assert_eq!(app.persisted_state().entries[&mario].notes, "4");
+    }
+
+    #[test]
+    fn activity_log_records_changes_per_entry_and_survives_a_reload() {
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let snes = EntryId::from_raw("console:snes");
+        let mut app = MemoryPakApp::default();
+        let start = chrono::Utc::now();
+
+        app.set_item_status(SetItemStatusInput {
+            id: mario.clone(),
+            owned: None,
+            favorite: None,
+            wishlist: Some(true),
+        })
+        .unwrap();
+        app.set_item_notes(SetItemNotesInput {
+            id: snes.clone(),
+            notes: "boxed".to_string(),
+        })
+        .unwrap();
+        // Setting a value it already has records nothing.
+        app.set_item_notes(SetItemNotesInput {
+            id: snes.clone(),
+            notes: "boxed".to_string(),
+        })
+        .unwrap();
+        app.undo().unwrap();
+
+        let history = app.entry_history(&snes);
+        assert_eq!(history.len(), 2);
+        assert!(history.iter().all(|event| event.at >= start));
+        assert_eq!(
+            history[1].change,
+            ActivityChange::Notes {
+                old: "boxed".to_string(),
+                new: String::new(),
+            }
+        );
+
+        let recent = app.recent_activity(ActivityQuery {
+            limit: Some(2),
+            since: None,
+        });
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].id, snes);
+        assert_eq!(
+            recent[0].name.as_deref(),
+            Some("Super Nintendo Entertainment System")
+        );
+        let wishlisted = app.recent_activity(ActivityQuery::default());
+        assert_eq!(wishlisted.len(), 3);
+        assert_eq!(
+            wishlisted[2].change,
+            ActivityChange::Wishlist {
+                old: false,
+                new: true
+            }
+        );
+
+        let json = serde_json::to_string(app.persisted_state()).unwrap();
+        let reloaded = MemoryPakApp::from_persisted_state(parse_persisted_state(&json).unwrap());
+        assert_eq!(reloaded.entry_history(&mario), app.entry_history(&mario));
+    }
+
+    #[test]
+    fn activity_policy_compacts_the_log() {
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let mut state = PersistedState::default();
+        let long_ago = chrono::DateTime::parse_from_rfc3339("2020-01-01T00:00:00Z")
+            .unwrap()
+            .to_utc();
+        state.activity.events.push(ActivityEvent {
+            at: long_ago,
+            id: mario.clone(),
+            change: ActivityChange::Favorite {
+                old: false,
+                new: true,
+            },
+        });
+        let mut app = MemoryPakApp::from_persisted_state(state);
+        assert_eq!(app.activity_policy(), None);
+
+        let policy = ActivityPolicy {
+            max_events: Some(2),
+            max_age_days: Some(365),
+        };
+        assert_eq!(app.set_activity_policy(Some(policy)), 1);
+        assert_eq!(app.activity_policy(), Some(policy));
+        for notes in ["a", "b", "c"] {
+            app.set_item_notes(SetItemNotesInput {
+                id: mario.clone(),
+                notes: notes.to_string(),
+            })
+            .unwrap();
+        }
+        let history = app.entry_history(&mario);
+        assert_eq!(history.len(), 2);
+        assert!(matches!(&history[1].change, ActivityChange::Notes { new, .. } if new == "c"));
+    }
+
+    #[test]
+    fn growth_timeline_buckets_owned_counts_and_spending() {
+        let date = |y, m, d| chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let bought = |on, price| OwnedCopy {
+            acquired_on: Some(on),
+            purchase_price: price,
+            currency: price.map(|_| "USD".to_string()),
+            ..Default::default()
+        };
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let snes = EntryId::from_raw("console:snes");
+        let nes = EntryId::from_raw("console:nes");
+        let figure = catalog().collectibles[0].clone();
+
+        // Owned before activity was recorded, so it has no date at all.
+        let json = r#"{"entries":{"game:nes/2-in-1-super-mario-bros-duck-hunt":{"owned":true}}}"#;
+        let mut app = MemoryPakApp::from_persisted_state(parse_persisted_state(json).unwrap());
+        let copies = [
+            (
+                &mario,
+                vec![
+                    bought(date(2024, 1, 10), Some(1500)),
+                    bought(date(2024, 3, 5), Some(2500)),
+                ],
+            ),
+            (&snes, vec![bought(date(2023, 12, 20), Some(10000))]),
+            (&figure.id, vec![bought(date(2024, 2, 14), None)]),
+        ];
+        for (id, copies) in copies {
+            app.set_item_copies(SetItemCopiesInput {
+                id: id.clone(),
+                copies,
+            })
+            .unwrap();
+        }
+        // No acquisition date: dated by the activity log, so today.
+        app.set_item_status(SetItemStatusInput {
+            id: nes.clone(),
+            owned: Some(true),
+            favorite: None,
+            wishlist: None,
+        })
+        .unwrap();
+
+        let report = app
+            .growth_timeline(TimelineInput {
+                bucket: TimeBucket::Month,
+                from: Some(date(2024, 1, 1)),
+                to: Some(date(2024, 3, 31)),
+            })
+            .unwrap();
+        assert_eq!(
+            report.buckets,
+            vec![date(2024, 1, 1), date(2024, 2, 1), date(2024, 3, 1)]
+        );
+        assert_eq!(report.undated_copies, 1);
+        let counts: Vec<_> = report
+            .total
+            .iter()
+            .map(|point| (point.acquired, point.owned, point.owned_copies))
+            .collect();
+        // The SNES from December is carried into January's totals.
+        assert_eq!(counts, vec![(1, 2, 2), (1, 3, 3), (1, 3, 4)]);
+        assert_eq!(report.total[0].spent[0].amount, 1500);
+        assert!(report.total[1].spent.is_empty());
+        assert_eq!(report.total[2].spent[0].amount, 2500);
+
+        let series: Vec<_> = report.by_console.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(series, vec![nes.as_str(), snes.as_str()]);
+        let nes_owned: Vec<_> = report.by_console[0]
+            .points
+            .iter()
+            .map(|point| point.owned_copies)
+            .collect();
+        assert_eq!(nes_owned, vec![1, 1, 2]);
+        assert_eq!(report.by_console[1].points[2].owned, 1);
+        assert_eq!(report.by_collection.len(), 1);
+        assert_eq!(report.by_collection[0].id, figure.collection_id);
+
+        let yearly = app
+            .growth_timeline(TimelineInput {
+                bucket: TimeBucket::Year,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(yearly.buckets[0], date(2023, 1, 1));
+        let today = yearly.total.last().unwrap();
+        assert_eq!((today.owned, today.owned_copies), (4, 5));
+        assert_eq!(
+            yearly.buckets.last(),
+            Some(&TimeBucket::Year.start_of(chrono::Utc::now().date_naive()))
+        );
+    }
+
+    #[test]
+    fn growth_timeline_range_is_bounded_despite_mistyped_dates() {
+        let typo = chrono::NaiveDate::from_ymd_opt(202, 5, 1).unwrap();
+        let mut app = MemoryPakApp::default();
+        app.set_item_copies(SetItemCopiesInput {
+            id: EntryId::from_raw("game:nes/super-mario-bros"),
+            copies: vec![OwnedCopy {
+                acquired_on: Some(typo),
+                ..Default::default()
+            }],
+        })
+        .unwrap();
+
+        let weekly = TimelineInput {
+            bucket: TimeBucket::Week,
+            ..Default::default()
+        };
+        let report = app.growth_timeline(weekly).unwrap();
+        assert!(report.buckets.len() <= MAX_TIMELINE_BUCKETS);
+        assert!(report.buckets[0] > chrono::NaiveDate::from_ymd_opt(1900, 1, 1).unwrap());
+        // The copy is still owned, just from before the first bucket.
+        assert_eq!(report.total[0].owned_copies, 1);
+        assert_eq!(report.total[0].acquired, 0);
+
+        let explicit = app.growth_timeline(TimelineInput {
+            from: Some(typo),
+            ..weekly
+        });
+        assert!(matches!(
+            explicit,
+            Err(CoreError::TimelineTooLong(MAX_TIMELINE_BUCKETS))
+        ));
+    }
+
+    #[test]
+    fn imported_lists_follow_the_strategy_and_show_in_the_preview() {
+        let mario = EntryId::from_raw("game:nes/super-mario-bros");
+        let snes = EntryId::from_raw("console:snes");
+        let nes = EntryId::from_raw("console:nes");
+        let list = |name: &str, entries: &[&EntryId]| SetListInput {
+            name: name.to_string(),
+            entries: entries.iter().map(|id| (*id).clone()).collect(),
+        };
+
+        let mut other = MemoryPakApp::default();
+        other.set_list(list("to sell", &[&snes, &nes])).unwrap();
+        other.set_list(list("grails", &[&mario])).unwrap();
+        let backup = other.export_json().unwrap();
+
+        let mut device = MemoryPakApp::default();
+        device.set_list(list("to sell", &[&mario, &snes])).unwrap();
+        let options = |strategy| ImportOptions {
+            mode: ImportMode::Merge,
+            strategy,
+        };
+        let members = |app: &MemoryPakApp| {
+            app.persisted_state()
+                .lists
+                .iter()
+                .find(|list| list.name == "to sell")
+                .unwrap()
+                .entries
+                .clone()
+        };
+
+        let keep = options(ImportStrategy::KeepLocal);
+        let preview = device.preview_import_json(&backup, keep).unwrap();
+        assert_eq!(preview.added_lists, vec!["grails".to_string()]);
+        assert!(preview.changed_lists.is_empty());
+        let mut kept = MemoryPakApp::from_persisted_state(device.persisted_state().clone());
+        kept.import_json(&backup, keep).unwrap();
+        assert_eq!(members(&kept), vec![mario.clone(), snes.clone()]);
+
+        let union = options(ImportStrategy::MergeFlags);
+        let preview = device.preview_import_json(&backup, union).unwrap();
+        assert_eq!(preview.changed_lists, vec!["to sell".to_string()]);
+        let mut merged = MemoryPakApp::from_persisted_state(device.persisted_state().clone());
+        merged.import_json(&backup, union).unwrap();
+        assert_eq!(
+            members(&merged),
+            vec![mario.clone(), snes.clone(), nes.clone()]
+        );
+
+        let overwrite = options(ImportStrategy::Overwrite);
+        device.import_json(&backup, overwrite).unwrap();
+        assert_eq!(members(&device), vec![snes.clone(), nes.clone()]);
+
+        device.set_list(list("wanted", &[&mario])).unwrap();
+        let replace = ImportOptions {
+            mode: ImportMode::Replace,
+            ..Default::default()
+        };
+        let preview = device.preview_import_json(&backup, replace).unwrap();
+        assert_eq!(preview.removed_lists, vec!["wanted".to_string()]);
+        assert!(preview.added_lists.is_empty() && preview.changed_lists.is_empty());
+    }
 }
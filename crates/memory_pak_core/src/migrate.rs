@@ -0,0 +1,139 @@
+//! Upgrades persisted state written by older builds to the current shape.
+//!
+//! Versions:
+//! - `0`: no `version` field. Entries carry the `owned`/`favorite`/
+//!   `wishlist`/`notes` flags, and later files also copies, play status,
+//!   tags and lists, all of which default when missing.
+//! - `1`: stamps `version`. A bare `owned` flag is stored as one default
+//!   copy and empty entries are dropped.
+
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::model::PersistedState;
+
+/// Schema version written by this build.
+pub const STATE_VERSION: u32 = 1;
+
+/// Step `i` upgrades version `i` to `i + 1`, in place.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[v0_to_v1];
+
+#[derive(Debug, Error)]
+pub enum StateError {
+    #[error("invalid persisted state: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("persisted state must be a JSON object")]
+    NotAnObject,
+    #[error("persisted state version {found} is newer than the supported version {supported}")]
+    TooNew { found: u64, supported: u32 },
+}
+
+/// Parses persisted state of any known version, upgrading it on the way.
+pub fn parse_persisted_state(json: &str) -> Result<PersistedState, StateError> {
+    migrate_state(serde_json::from_str(json)?)
+}
+
+pub fn migrate_state(value: Value) -> Result<PersistedState, StateError> {
+    let Value::Object(mut object) = value else {
+        return Err(StateError::NotAnObject);
+    };
+    let found = object.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if found > u64::from(STATE_VERSION) {
+        return Err(StateError::TooNew {
+            found,
+            supported: STATE_VERSION,
+        });
+    }
+    for step in &MIGRATIONS[found as usize..] {
+        step(&mut object);
+    }
+    object.insert("version".to_string(), json!(STATE_VERSION));
+    Ok(serde_json::from_value(Value::Object(object))?)
+}
+
+fn v0_to_v1(state: &mut Map<String, Value>) {
+    let Some(Value::Object(entries)) = state.get_mut("entries") else {
+        return;
+    };
+    entries.retain(|_, entry| {
+        let Value::Object(entry) = entry else {
+            return false;
+        };
+        let owned = entry.get("owned").and_then(Value::as_bool) == Some(true);
+        let has_copies = entry
+            .get("copies")
+            .and_then(Value::as_array)
+            .is_some_and(|copies| !copies.is_empty());
+        if owned && !has_copies {
+            entry.insert("copies".to_string(), json!([{}]));
+        }
+        entry.values().any(|value| match value {
+            Value::Null | Value::Bool(false) => false,
+            Value::String(s) => !s.is_empty(),
+            Value::Array(a) => !a.is_empty(),
+            _ => true,
+        })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::EntryId;
+    use crate::model::{OwnedCopy, PlayStatus};
+
+    fn fixture(name: &str) -> PersistedState {
+        let json = match name {
+            "v0-flags" => include_str!("../fixtures/state/v0-flags.json"),
+            "v0-copies" => include_str!("../fixtures/state/v0-copies.json"),
+            "v1" => include_str!("../fixtures/state/v1.json"),
+            _ => unreachable!(),
+        };
+        parse_persisted_state(json).expect(name)
+    }
+
+    #[test]
+    fn v0_flags_only_state_gains_copies_and_drops_empty_entries() {
+        let state = fixture("v0-flags");
+        assert_eq!(state.version, STATE_VERSION);
+        assert_eq!(state.entries.len(), 3);
+        let mario = &state.entries[&EntryId::from_raw("game:nes/super-mario-bros")];
+        assert!(mario.owned && mario.favorite);
+        assert_eq!(mario.copies, vec![OwnedCopy::default()]);
+        let snes = &state.entries[&EntryId::from_raw("console:snes")];
+        assert!(snes.copies.is_empty());
+        assert_eq!(snes.notes, "boxed");
+        assert!(!state
+            .entries
+            .contains_key(&EntryId::from_raw("collectible:legodimensions/aquaman")));
+    }
+
+    #[test]
+    fn v0_state_with_copies_keeps_its_details() {
+        let state = fixture("v0-copies");
+        let mario = &state.entries[&EntryId::from_raw("game:nes/super-mario-bros")];
+        assert_eq!(mario.copies.len(), 2);
+        assert_eq!(mario.copies[0].purchase_price, Some(2500));
+        assert_eq!(mario.play_status, Some(PlayStatus::Beaten));
+        assert_eq!(mario.tags, vec!["lent out".to_string()]);
+        assert_eq!(state.lists.len(), 1);
+    }
+
+    #[test]
+    fn current_version_round_trips_unchanged() {
+        let state = fixture("v1");
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(parse_persisted_state(&json).unwrap(), state);
+        assert!(json.contains(r#""version":1"#));
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let err = parse_persisted_state(r#"{"version":99,"entries":{}}"#).unwrap_err();
+        assert!(matches!(err, StateError::TooNew { found: 99, .. }));
+        assert!(matches!(
+            parse_persisted_state("[]"),
+            Err(StateError::NotAnObject)
+        ));
+    }
+}
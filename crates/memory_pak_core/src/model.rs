@@ -1,8 +1,13 @@
 use std::collections::HashMap;
 
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+use crate::activity::ActivityLog;
+use crate::aliases::IdReport;
 use crate::ids::{EntryId, EntryKind};
+use crate::migrate::STATE_VERSION;
+use crate::search_index::SearchIndex;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -27,6 +32,66 @@ pub struct Game {
     pub developer: String,
     pub publisher: String,
     pub year: u32,
+    pub releases: Releases,
+}
+
+/// Release regions as they appear in `database/games/*.json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Region {
+    Jp,
+    Na,
+    /// PAL territories. Also matches the separate `eu` and `au` columns some
+    /// source lists use instead of a single PAL column.
+    Pal,
+    Eu,
+    Au,
+    Br,
+}
+
+/// Per-region release dates as ISO `YYYY`, `YYYY-MM` or `YYYY-MM-DD` strings.
+///
+/// Partial dates compare correctly as strings, so sorting needs no parsing.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Releases {
+    #[serde(default)]
+    pub jp: Option<String>,
+    #[serde(default)]
+    pub na: Option<String>,
+    #[serde(default)]
+    pub pal: Option<String>,
+    #[serde(default)]
+    pub eu: Option<String>,
+    #[serde(default)]
+    pub au: Option<String>,
+    #[serde(default)]
+    pub br: Option<String>,
+}
+
+impl Releases {
+    /// Earliest release date in `region`, if the title came out there.
+    pub fn date_in(&self, region: Region) -> Option<&str> {
+        match region {
+            Region::Jp => self.jp.as_deref(),
+            Region::Na => self.na.as_deref(),
+            Region::Pal => [&self.pal, &self.eu, &self.au]
+                .into_iter()
+                .filter_map(|date| date.as_deref())
+                .min(),
+            Region::Eu => self.eu.as_deref(),
+            Region::Au => self.au.as_deref(),
+            Region::Br => self.br.as_deref(),
+        }
+    }
+
+    /// Earliest release date in any region.
+    pub fn earliest(&self) -> Option<&str> {
+        [&self.jp, &self.na, &self.pal, &self.eu, &self.au, &self.br]
+            .into_iter()
+            .filter_map(|date| date.as_deref())
+            .min()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -50,9 +115,70 @@ pub struct Collectible {
     pub year: u8,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Completeness {
+    Loose,
+    /// Complete in box.
+    Cib,
+    Sealed,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Condition {
+    Mint,
+    NearMint,
+    VeryGood,
+    Good,
+    Fair,
+    Poor,
+}
+
+/// One physical copy of an entry. Unknown details stay `None`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnedCopy {
+    #[serde(default)]
+    pub condition: Option<Condition>,
+    #[serde(default)]
+    pub completeness: Option<Completeness>,
+    #[serde(default)]
+    pub region: Option<Region>,
+    #[serde(default)]
+    pub notes: String,
+    /// Price paid in minor units of `currency` (cents, pence, yen).
+    #[serde(default)]
+    pub purchase_price: Option<u64>,
+    /// ISO 4217 code such as `USD`, matched case-insensitively in reports.
+    /// Prices without one are totalled under `""`.
+    #[serde(default)]
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub acquired_on: Option<NaiveDate>,
+    /// Where the copy came from: a shop, eBay, a trade, a gift, ...
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+/// Where a game sits in the player's queue, independent of ownership.
+///
+/// Variant order is the `SortKey::PlayStatus` order.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum PlayStatus {
+    Playing,
+    Backlog,
+    Beaten,
+    Completed,
+    Abandoned,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct EntryState {
+    /// Mirrors `!copies.is_empty()`. Kept on the wire for the UI toggle and
+    /// for state files written before copies existed.
     #[serde(default)]
     pub owned: bool,
     #[serde(default)]
@@ -61,18 +187,80 @@ pub struct EntryState {
     pub wishlist: bool,
     #[serde(default)]
     pub notes: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub copies: Vec<OwnedCopy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub play_status: Option<PlayStatus>,
+    /// When the game was beaten or completed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_on: Option<NaiveDate>,
+    /// Free-form labels such as `to sell` or `lent out`. Trimmed and unique.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 impl EntryState {
     pub fn is_empty(&self) -> bool {
-        !self.owned && !self.favorite && !self.wishlist && self.notes.is_empty()
+        !self.owned
+            && !self.favorite
+            && !self.wishlist
+            && self.notes.is_empty()
+            && self.copies.is_empty()
+            && self.play_status.is_none()
+            && self.completed_on.is_none()
+            && self.tags.is_empty()
+    }
+
+    /// Reconciles `owned` with `copies`: a bare `owned` flag becomes one
+    /// default copy, and any copies imply `owned`.
+    pub fn normalize_copies(&mut self) {
+        if self.owned && self.copies.is_empty() {
+            self.copies.push(OwnedCopy::default());
+        }
+        self.owned = !self.copies.is_empty();
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+/// Read it with [`parse_persisted_state`](crate::parse_persisted_state) so
+/// files written by older builds are upgraded first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PersistedState {
+    /// Schema version; see [`STATE_VERSION`].
+    #[serde(default)]
+    pub version: u32,
     #[serde(default)]
     pub entries: HashMap<EntryId, EntryState>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lists: Vec<EntryList>,
+    #[serde(default, skip_serializing_if = "ActivityLog::is_empty")]
+    pub activity: ActivityLog,
+}
+
+impl Default for PersistedState {
+    fn default() -> Self {
+        Self {
+            version: STATE_VERSION,
+            entries: HashMap::new(),
+            lists: Vec::new(),
+            activity: ActivityLog::default(),
+        }
+    }
+}
+
+/// A user-named, ordered selection of entries of any kind.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryList {
+    pub name: String,
+    #[serde(default)]
+    pub entries: Vec<EntryId>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -81,6 +269,69 @@ pub struct Catalog {
     pub games: HashMap<EntryId, Game>,
     pub collections: Vec<Collection>,
     pub collectibles: Vec<Collectible>,
+    pub(crate) console_index: HashMap<EntryId, usize>,
+    pub(crate) collectible_index: HashMap<EntryId, usize>,
+    pub(crate) search: SearchIndex,
+    /// Retired ids mapped to their live replacement.
+    pub(crate) aliases: HashMap<EntryId, EntryId>,
+}
+
+impl Catalog {
+    /// Looks up any catalog entry by id.
+    pub fn get(&self, id: &EntryId) -> Option<CatalogEntry<'_>> {
+        match id.kind()? {
+            EntryKind::Console => self.console(id).map(CatalogEntry::Console),
+            EntryKind::Game => self.games.get(id).map(CatalogEntry::Game),
+            EntryKind::Collectible => self.collectible(id).map(CatalogEntry::Collectible),
+        }
+    }
+
+    /// The live id for `id`: itself when it is in the catalog, its alias
+    /// target when it was renamed, and `None` otherwise.
+    pub fn resolve<'a>(&'a self, id: &'a EntryId) -> Option<&'a EntryId> {
+        if self.get(id).is_some() {
+            Some(id)
+        } else {
+            self.aliases.get(id)
+        }
+    }
+
+    pub fn console(&self, id: &EntryId) -> Option<&Console> {
+        self.console_index.get(id).map(|&i| &self.consoles[i])
+    }
+
+    pub fn collectible(&self, id: &EntryId) -> Option<&Collectible> {
+        self.collectible_index
+            .get(id)
+            .map(|&i| &self.collectibles[i])
+    }
+}
+
+/// A catalog entry of any kind, as returned by [`Catalog::get`].
+#[derive(Debug, Clone, Copy)]
+pub enum CatalogEntry<'a> {
+    Console(&'a Console),
+    Game(&'a Game),
+    Collectible(&'a Collectible),
+}
+
+impl<'a> CatalogEntry<'a> {
+    /// Display name: the console or collectible name, or the game title.
+    pub fn name(&self) -> &'a str {
+        match self {
+            CatalogEntry::Console(console) => &console.name,
+            CatalogEntry::Game(game) => &game.title,
+            CatalogEntry::Collectible(collectible) => &collectible.name,
+        }
+    }
+
+    pub fn kind(&self) -> EntryKind {
+        match self {
+            CatalogEntry::Console(_) => EntryKind::Console,
+            CatalogEntry::Game(_) => EntryKind::Game,
+            CatalogEntry::Collectible(_) => EntryKind::Collectible,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -111,12 +362,14 @@ impl From<ItemKind> for EntryKind {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct ConsoleCounts {
     pub owned: usize,
+    pub owned_copies: usize,
     pub favorite: usize,
     pub wishlist: usize,
+    pub backlog: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -144,6 +397,7 @@ pub struct GameView {
     pub publisher: String,
     pub console_id: EntryId,
     pub console_name: String,
+    pub releases: Releases,
     pub state: EntryState,
 }
 
@@ -162,6 +416,16 @@ pub struct CollectibleView {
     pub state: EntryState,
 }
 
+/// The view for a single entry of any kind. Serializes as the inner view,
+/// whose `kind` field tells them apart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ItemView {
+    Console(ConsoleView),
+    Game(GameView),
+    Collectible(CollectibleView),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CollectionView {
@@ -173,19 +437,22 @@ pub struct CollectionView {
     pub owned: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct CollectionStats {
     pub total_consoles: usize,
     pub owned_consoles: usize,
+    pub owned_console_copies: usize,
     pub favorite_consoles: usize,
     pub wishlist_consoles: usize,
     pub total_games: usize,
     pub owned_games: usize,
+    pub owned_game_copies: usize,
     pub favorite_games: usize,
     pub wishlist_games: usize,
     pub total_collectibles: usize,
     pub owned_collectibles: usize,
+    pub owned_collectible_copies: usize,
     pub favorite_collectibles: usize,
     pub wishlist_collectibles: usize,
 }
@@ -200,6 +467,11 @@ pub struct InitialState {
     pub collections: Vec<CollectionView>,
     pub total_games: usize,
     pub total_collectibles: usize,
+    /// Every tag in use, sorted, with the number of entries carrying it.
+    pub tags: Vec<TagCount>,
+    pub lists: Vec<EntryList>,
+    /// Ids renamed or left unresolved while loading the saved state.
+    pub id_report: IdReport,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -209,3 +481,42 @@ pub struct MutationResult {
     pub state: EntryState,
     pub stats: CollectionStats,
 }
+
+/// Money spent in one currency. `amount` is in minor units.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendTotal {
+    pub currency: String,
+    pub amount: u64,
+    /// Priced copies that contributed to `amount`.
+    pub copies: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendGroup {
+    /// Console `EntryId` or collection id.
+    pub id: String,
+    pub name: String,
+    pub totals: Vec<SpendTotal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct YearSpend {
+    pub year: i32,
+    pub totals: Vec<SpendTotal>,
+}
+
+/// Purchase totals across every priced copy, grouped several ways.
+///
+/// Console groups include both the console hardware and its games. Copies
+/// without an acquisition date count towards every total except `by_year`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendingReport {
+    pub totals: Vec<SpendTotal>,
+    pub by_console: Vec<SpendGroup>,
+    pub by_collection: Vec<SpendGroup>,
+    pub by_year: Vec<YearSpend>,
+}
@@ -0,0 +1,26 @@
+// Shared between build.rs and src/ids.rs.
+//
+// Keep this module dependency-free apart from unicode-normalization so the
+// build script can `#[path = "src/normalize.rs"] mod normalize;` it directly
+// and index the catalog with exactly the normalization queries use.
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Lowercase, Unicode-folded, punctuation-stripped text suitable for substring search.
+pub fn normalize_for_search(value: &str) -> String {
+    value
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .filter(|c| !matches!(*c, '\'' | ':' | '-' | '_' | '.' | ',' | '!' | '?' | ';'))
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+fn is_combining_mark(c: char) -> bool {
+    let code = c as u32;
+    (0x0300..=0x036F).contains(&code)
+        || (0x1AB0..=0x1AFF).contains(&code)
+        || (0x1DC0..=0x1DFF).contains(&code)
+        || (0x20D0..=0x20FF).contains(&code)
+        || (0xFE20..=0xFE2F).contains(&code)
+}
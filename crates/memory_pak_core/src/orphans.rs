@@ -0,0 +1,247 @@
+//! Entries saved under ids the catalog no longer has, and their repair.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ids::{EntryId, EntryKind};
+use crate::import_export::{merge_entry, ImportStrategy};
+use crate::model::{Catalog, CatalogEntry, CollectionStats, EntryState, PersistedState};
+
+/// Suggestions kept per orphan.
+const MAX_SUGGESTIONS: usize = 5;
+/// Lowest slug similarity, in percent, worth suggesting.
+const MIN_SIMILARITY: u32 = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanEntry {
+    pub id: EntryId,
+    /// Empty when the id only survives as a list member.
+    pub state: EntryState,
+    /// Names of the lists that still hold the id.
+    pub lists: Vec<String>,
+    /// Best first.
+    pub suggestions: Vec<OrphanSuggestion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanSuggestion {
+    pub id: EntryId,
+    /// Game title, or console or collectible name.
+    pub title: String,
+    /// Slug similarity from 0 to 100.
+    pub score: u32,
+}
+
+/// What to do with one orphaned id.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum OrphanRepair {
+    /// Moves the saved state and list memberships onto a catalog entry.
+    /// Where both are set, the catalog entry's own values win.
+    Remap { id: EntryId, to: EntryId },
+    /// Drops the saved state and removes the id from every list.
+    Discard { id: EntryId },
+}
+
+impl OrphanRepair {
+    pub fn id(&self) -> &EntryId {
+        match self {
+            OrphanRepair::Remap { id, .. } | OrphanRepair::Discard { id } => id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanRepairSummary {
+    pub stats: CollectionStats,
+    pub remapped: usize,
+    pub discarded: usize,
+}
+
+pub(crate) fn is_orphan(catalog: &Catalog, state: &PersistedState, id: &EntryId) -> bool {
+    catalog.get(id).is_none()
+        && (state.entries.contains_key(id) || state.lists.iter().any(|l| l.entries.contains(id)))
+}
+
+/// Every orphaned id in `state`, sorted, with replacement suggestions.
+pub(crate) fn find_orphans(catalog: &Catalog, state: &PersistedState) -> Vec<OrphanEntry> {
+    let mut orphans: BTreeMap<&EntryId, OrphanEntry> = BTreeMap::new();
+    let orphan = |id: &EntryId| -> Option<OrphanEntry> {
+        catalog.get(id).is_none().then(|| OrphanEntry {
+            id: id.clone(),
+            state: state.entries.get(id).cloned().unwrap_or_default(),
+            lists: Vec::new(),
+            suggestions: Vec::new(),
+        })
+    };
+
+    for id in state.entries.keys() {
+        if let Some(entry) = orphan(id) {
+            orphans.insert(id, entry);
+        }
+    }
+    for list in &state.lists {
+        for id in &list.entries {
+            if !orphans.contains_key(id) {
+                let Some(entry) = orphan(id) else {
+                    continue;
+                };
+                orphans.insert(id, entry);
+            }
+            orphans
+                .get_mut(id)
+                .expect("inserted above")
+                .lists
+                .push(list.name.clone());
+        }
+    }
+
+    orphans
+        .into_values()
+        .map(|mut entry| {
+            entry.suggestions = suggest_replacements(catalog, &entry.id);
+            entry
+        })
+        .collect()
+}
+
+/// Catalog entries of the same kind whose id looks like `id`, ranked by
+/// bigram similarity of the slugs. A trailing `~N` duplicate suffix is
+/// ignored so a shifted duplicate still finds its siblings.
+fn suggest_replacements(catalog: &Catalog, id: &EntryId) -> Vec<OrphanSuggestion> {
+    let Some(kind) = id.kind() else {
+        return Vec::new();
+    };
+    let target = bigrams(slug_of(id));
+    if target.is_empty() {
+        return Vec::new();
+    }
+
+    let candidates: Box<dyn Iterator<Item = CatalogEntry<'_>>> = match kind {
+        EntryKind::Console => Box::new(catalog.consoles.iter().map(CatalogEntry::Console)),
+        EntryKind::Game => Box::new(catalog.games.values().map(CatalogEntry::Game)),
+        EntryKind::Collectible => {
+            Box::new(catalog.collectibles.iter().map(CatalogEntry::Collectible))
+        }
+    };
+    let mut scored: Vec<OrphanSuggestion> = candidates
+        .filter_map(|entry| {
+            let (candidate, title) = match entry {
+                CatalogEntry::Console(c) => (&c.id, &c.name),
+                CatalogEntry::Game(g) => (&g.id, &g.title),
+                CatalogEntry::Collectible(c) => (&c.id, &c.name),
+            };
+            let score = similarity(&target, &bigrams(slug_of(candidate)));
+            (score >= MIN_SIMILARITY).then(|| OrphanSuggestion {
+                id: candidate.clone(),
+                title: title.clone(),
+                score,
+            })
+        })
+        .collect();
+    scored.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.id.cmp(&b.id)));
+    scored.truncate(MAX_SUGGESTIONS);
+    scored
+}
+
+/// The id without its kind prefix or duplicate suffix: `nes/super-mario-bros`.
+fn slug_of(id: &EntryId) -> &str {
+    let locator = id.as_str().split_once(':').map_or("", |(_, rest)| rest);
+    match locator.rsplit_once('~') {
+        Some((base, n)) if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) => base,
+        _ => locator,
+    }
+}
+
+fn bigrams(slug: &str) -> Vec<(char, char)> {
+    let chars: Vec<char> = slug.chars().collect();
+    let mut pairs: Vec<(char, char)> = chars.windows(2).map(|w| (w[0], w[1])).collect();
+    pairs.sort_unstable();
+    pairs
+}
+
+/// Sørensen–Dice coefficient of two sorted bigram multisets, in percent.
+fn similarity(a: &[(char, char)], b: &[(char, char)]) -> u32 {
+    let (mut i, mut j, mut shared) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                shared += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    (200 * shared / (a.len() + b.len()).max(1)) as u32
+}
+
+/// Applies repairs that were already validated against `catalog`.
+pub(crate) fn apply_repairs(
+    state: &mut PersistedState,
+    repairs: Vec<OrphanRepair>,
+) -> (usize, usize) {
+    let (mut remapped, mut discarded) = (0, 0);
+    for repair in repairs {
+        let orphaned = state.entries.remove(repair.id());
+        match &repair {
+            OrphanRepair::Remap { id, to } => {
+                if let Some(orphaned) = orphaned {
+                    let live = state.entries.entry(to.clone()).or_default();
+                    *live = merge_entry(live, orphaned, ImportStrategy::MergeFlags);
+                }
+                for list in &mut state.lists {
+                    let mut seen = HashSet::new();
+                    list.entries = list
+                        .entries
+                        .iter()
+                        .map(|member| if member == id { to } else { member })
+                        .filter(|member| seen.insert(*member))
+                        .cloned()
+                        .collect();
+                }
+                remapped += 1;
+            }
+            OrphanRepair::Discard { id } => {
+                for list in &mut state.lists {
+                    list.entries.retain(|member| member != id);
+                }
+                discarded += 1;
+            }
+        }
+    }
+    (remapped, discarded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugs_drop_kind_and_duplicate_suffix() {
+        assert_eq!(
+            slug_of(&EntryId::from_raw("game:nes/tetris~2")),
+            "nes/tetris"
+        );
+        assert_eq!(
+            slug_of(&EntryId::from_raw("game:nes/tetris~")),
+            "nes/tetris~"
+        );
+        assert_eq!(slug_of(&EntryId::from_raw("console:snes")), "snes");
+    }
+
+    #[test]
+    fn similarity_is_symmetric_and_bounded() {
+        let a = bigrams("nes/super-mario-bros");
+        let b = bigrams("nes/super-mario-bros-1");
+        assert_eq!(similarity(&a, &a), 100);
+        assert_eq!(similarity(&a, &b), similarity(&b, &a));
+        assert!(similarity(&a, &b) > 80);
+        assert!(similarity(&a, &bigrams("psx/crash")) < MIN_SIMILARITY);
+    }
+}
@@ -1,43 +1,57 @@
+//! The on-disk `state.json` shared by the desktop app and the CLI.
+
 use std::fs;
 use std::io::{self, ErrorKind};
 use std::path::{Path, PathBuf};
 
 use directories::ProjectDirs;
-use memory_pak_core::PersistedState;
+
+use crate::migrate::parse_persisted_state;
+use crate::model::PersistedState;
 
 const STATE_FILE: &str = "state.json";
 
-pub fn load_persisted_state() -> io::Result<PersistedState> {
-    let Some(path) = state_path()? else {
-        return Ok(PersistedState::default());
+/// `state.json` under the OS data directory, created if missing. `None` on
+/// platforms without a home directory.
+pub fn default_state_path() -> io::Result<Option<PathBuf>> {
+    let Some(dirs) = ProjectDirs::from("com", "memorypak", "memory_pak") else {
+        return Ok(None);
     };
+    let data_dir = dirs.data_dir();
+    fs::create_dir_all(data_dir)?;
+    Ok(Some(data_dir.join(STATE_FILE)))
+}
 
-    match fs::read_to_string(&path) {
-        Ok(text) => serde_json::from_str::<PersistedState>(&text)
-            .map_err(|err| io::Error::new(ErrorKind::InvalidData, err)),
-        Err(err) if err.kind() == ErrorKind::NotFound => Ok(PersistedState::default()),
-        Err(err) => Err(err),
+pub fn load_persisted_state() -> io::Result<PersistedState> {
+    match default_state_path()? {
+        Some(path) => load_persisted_state_from(&path),
+        None => Ok(PersistedState::default()),
     }
 }
 
 pub fn save_persisted_state(state: &PersistedState) -> io::Result<()> {
-    let Some(path) = state_path()? else {
-        return Ok(());
-    };
+    match default_state_path()? {
+        Some(path) => save_persisted_state_to(&path, state),
+        None => Ok(()),
+    }
+}
 
+/// Reads and upgrades the state at `path`. A missing file is an empty state.
+pub fn load_persisted_state_from(path: &Path) -> io::Result<PersistedState> {
+    match fs::read_to_string(path) {
+        Ok(text) => {
+            parse_persisted_state(&text).map_err(|err| io::Error::new(ErrorKind::InvalidData, err))
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(PersistedState::default()),
+        Err(err) => Err(err),
+    }
+}
+
+pub fn save_persisted_state_to(path: &Path, state: &PersistedState) -> io::Result<()> {
     let json = serde_json::to_string_pretty(state)
         .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
 
-    write_atomic(&path, json.as_bytes())
-}
-
-fn state_path() -> io::Result<Option<PathBuf>> {
-    let Some(dirs) = ProjectDirs::from("com", "memorypak", "memory_pak") else {
-        return Ok(None);
-    };
-    let data_dir = dirs.data_dir();
-    fs::create_dir_all(data_dir)?;
-    Ok(Some(data_dir.join(STATE_FILE)))
+    write_atomic(path, json.as_bytes())
 }
 
 fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
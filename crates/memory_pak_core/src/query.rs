@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+use crate::fuzzy::text_score;
 use crate::ids::normalize_for_search;
-use crate::model::EntryState;
+use crate::model::{EntryState, PlayStatus, Region};
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -12,6 +13,11 @@ pub enum FilterBy {
     Favorites,
     Wishlist,
     NotOwned,
+    Backlog,
+    Playing,
+    Beaten,
+    Completed,
+    Abandoned,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -26,6 +32,13 @@ pub enum SortKey {
     Collection,
     Variant,
     Manufacturer,
+    /// Release date in `QueryInput::region`, or the earliest release in any
+    /// region when no region is set. Undated titles sort last.
+    ReleaseDate,
+    /// `PlayStatus` order, entries without a play status last.
+    PlayStatus,
+    /// Best search match first. The default whenever the search has text.
+    Relevance,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -41,6 +54,15 @@ pub struct QueryInput {
     pub console_id: Option<String>,
     #[serde(default)]
     pub collection_id: Option<String>,
+    /// Only games released in this region. Ignored for consoles and collectibles.
+    #[serde(default)]
+    pub region: Option<Region>,
+    /// Only entries carrying every one of these tags.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Only entries in the custom list with this name.
+    #[serde(default)]
+    pub list: Option<String>,
     #[serde(default)]
     pub offset: Option<usize>,
     #[serde(default)]
@@ -52,16 +74,421 @@ pub struct QueryInput {
 pub struct QueryResult<T> {
     pub total: usize,
     pub items: Vec<T>,
+    /// Set when `search` failed to parse; `items` is then empty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<QuerySyntaxError>,
 }
 
-pub(crate) fn normalized_query(value: Option<&str>) -> Option<String> {
-    let raw = value?;
-    let normalized = normalize_for_search(raw);
-    let trimmed = normalized.trim();
-    if trimmed.is_empty() {
-        None
-    } else {
-        Some(trimmed.to_string())
+impl<T> QueryResult<T> {
+    pub(crate) fn invalid(error: QuerySyntaxError) -> Self {
+        Self {
+            total: 0,
+            items: Vec::new(),
+            error: Some(error),
+        }
+    }
+}
+
+/// A search box parse failure. `start..end` are character offsets into the
+/// original search string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[serde(rename_all = "camelCase")]
+#[error("{message} (at {start}..{end})")]
+pub struct QuerySyntaxError {
+    pub start: usize,
+    pub end: usize,
+    pub message: String,
+}
+
+/// Parsed search box input. Every clause must match.
+///
+/// Syntax, space separated:
+/// - `mario` or `"super mario"`: text in any searchable field
+/// - `publisher:capcom`, `console:snes`, `title:"final fantasy"`: text in one field
+/// - `year:1991`, `year:1990..1995`, `year:..1995`, `year:1990..`
+/// - `tag:rare`, `region:pal`, `is:owned`, `is:backlog`
+/// - `owned`, `favorite`, `wishlist`: shorthand for `is:…`; quote to search the word
+/// - a leading `-` negates any clause
+///
+/// Unqualified text tolerates small typos (`castelvania`, `ocarnia`) and each
+/// word matches on its own, so word order does not matter outside quotes.
+/// Field-qualified values match exactly.
+///
+/// Unknown `word:` prefixes are searched as plain text so titles such as
+/// `Zelda: Ocarina` keep working.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub clauses: Vec<SearchClause>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchClause {
+    pub negated: bool,
+    pub term: SearchTerm,
+}
+
+/// Text values are stored normalized with `normalize_for_search`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchTerm {
+    Text(String),
+    Field(SearchField, String),
+    Year { min: Option<u32>, max: Option<u32> },
+    Tag(String),
+    Region(Region),
+    Status(StatusFlag),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchField {
+    /// Game title, or console / collectible name.
+    Title,
+    Publisher,
+    Developer,
+    /// Console name, short id or abbreviation.
+    Console,
+    Collection,
+    Category,
+    Group,
+    Variant,
+    Manufacturer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFlag {
+    Owned,
+    Favorite,
+    Wishlist,
+    Play(PlayStatus),
+}
+
+/// What a search clause can ask of a catalog row.
+pub(crate) trait SearchTarget {
+    /// Calls `visit` with the normalized text of `field`, or of every default
+    /// search field when `field` is `None`. The flag marks the row's primary
+    /// name, which ranks above secondary fields.
+    fn visit_text(&self, field: Option<SearchField>, visit: &mut dyn FnMut(&str, bool));
+    fn year(&self) -> Option<u32>;
+    fn released_in(&self, _region: Region) -> bool {
+        false
+    }
+    fn state(&self) -> &EntryState;
+}
+
+impl SearchQuery {
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// Whether any clause searches text, which makes relevance meaningful.
+    pub fn has_text(&self) -> bool {
+        self.clauses.iter().any(|clause| {
+            !clause.negated && matches!(clause.term, SearchTerm::Text(_) | SearchTerm::Field(..))
+        })
+    }
+
+    /// Relevance of `target`, or `None` when any clause rejects it.
+    ///
+    /// Unqualified text tolerates typos; negated clauses only reject exact
+    /// substring matches so a typo never hides an unrelated row.
+    pub(crate) fn score(&self, target: &impl SearchTarget) -> Option<u32> {
+        let mut total = 0;
+        for clause in &self.clauses {
+            if clause.negated {
+                if clause.term.matches_exactly(target) {
+                    return None;
+                }
+            } else {
+                total += clause.term.score(target)?;
+            }
+        }
+        Some(total)
+    }
+}
+
+impl SearchTerm {
+    fn score(&self, target: &impl SearchTarget) -> Option<u32> {
+        match self {
+            SearchTerm::Text(needle) => best_text_score(target, None, needle, true),
+            SearchTerm::Field(field, needle) => {
+                best_text_score(target, Some(*field), needle, false)
+            }
+            _ => self.matches_exactly(target).then_some(0),
+        }
+    }
+
+    fn matches_exactly(&self, target: &impl SearchTarget) -> bool {
+        match self {
+            SearchTerm::Text(needle) => any_text_contains(target, None, needle),
+            SearchTerm::Field(field, needle) => any_text_contains(target, Some(*field), needle),
+            SearchTerm::Year { min, max } => target.year().is_some_and(|year| {
+                min.is_none_or(|min| year >= min) && max.is_none_or(|max| year <= max)
+            }),
+            SearchTerm::Tag(tag) => target
+                .state()
+                .tags
+                .iter()
+                .any(|t| normalize_for_search(t) == *tag),
+            SearchTerm::Region(region) => target.released_in(*region),
+            SearchTerm::Status(flag) => {
+                let state = target.state();
+                match flag {
+                    StatusFlag::Owned => state.owned,
+                    StatusFlag::Favorite => state.favorite,
+                    StatusFlag::Wishlist => state.wishlist,
+                    StatusFlag::Play(status) => state.play_status == Some(*status),
+                }
+            }
+        }
+    }
+}
+
+fn best_text_score(
+    target: &impl SearchTarget,
+    field: Option<SearchField>,
+    needle: &str,
+    typos: bool,
+) -> Option<u32> {
+    let mut best = None;
+    target.visit_text(field, &mut |text, primary| {
+        best = best.max(text_score(text, needle, primary, typos));
+    });
+    best
+}
+
+fn any_text_contains(target: &impl SearchTarget, field: Option<SearchField>, needle: &str) -> bool {
+    let mut found = false;
+    target.visit_text(field, &mut |text, _| found |= text.contains(needle));
+    found
+}
+
+/// Parses an optional search string. `None` and blank input parse to an
+/// empty query that matches everything.
+pub fn parse_search(input: Option<&str>) -> Result<SearchQuery, QuerySyntaxError> {
+    let Some(input) = input else {
+        return Ok(SearchQuery::default());
+    };
+    Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+    }
+    .parse()
+}
+
+enum Key {
+    Field(SearchField),
+    Year,
+    Tag,
+    Region,
+    Is,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn parse(mut self) -> Result<SearchQuery, QuerySyntaxError> {
+        let mut clauses = Vec::new();
+        loop {
+            while self.peek().is_some_and(char::is_whitespace) {
+                self.pos += 1;
+            }
+            if self.peek().is_none() {
+                break;
+            }
+            if let Some(clause) = self.clause()? {
+                clauses.push(clause);
+            }
+        }
+        Ok(SearchQuery { clauses })
+    }
+
+    fn clause(&mut self) -> Result<Option<SearchClause>, QuerySyntaxError> {
+        let start = self.pos;
+        let negated = self.peek() == Some('-');
+        if negated {
+            self.pos += 1;
+            if self.peek().is_none_or(char::is_whitespace) {
+                return Err(error(start, self.pos, "expected a term after `-`"));
+            }
+        }
+
+        if let Some((key, name)) = self.key() {
+            let key_start = self.pos;
+            self.pos += name.chars().count() + 1;
+            let value_start = self.pos;
+            let (value, _) = self.value()?;
+            if value.trim().is_empty() {
+                return Err(error(
+                    key_start,
+                    self.pos,
+                    format!("expected a value after `{name}:`"),
+                ));
+            }
+            let term = key_term(key, &value, value_start, self.pos)?;
+            return Ok(Some(SearchClause { negated, term }));
+        }
+
+        let (value, quoted) = self.value()?;
+        if !quoted {
+            if let Some(flag) = status_flag(&value).filter(|flag| {
+                matches!(
+                    flag,
+                    StatusFlag::Owned | StatusFlag::Favorite | StatusFlag::Wishlist
+                )
+            }) {
+                return Ok(Some(SearchClause {
+                    negated,
+                    term: SearchTerm::Status(flag),
+                }));
+            }
+        }
+        let normalized = normalize_for_search(&value);
+        let normalized = normalized.trim();
+        if normalized.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(SearchClause {
+            negated,
+            term: SearchTerm::Text(normalized.to_string()),
+        }))
+    }
+
+    /// A known `name:` prefix at the cursor. Does not consume input.
+    fn key(&self) -> Option<(Key, String)> {
+        let name: String = self.chars[self.pos..]
+            .iter()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .collect();
+        if self.chars.get(self.pos + name.len()) != Some(&':') {
+            return None;
+        }
+        let key = match name.to_ascii_lowercase().as_str() {
+            "title" | "name" => Key::Field(SearchField::Title),
+            "publisher" | "pub" => Key::Field(SearchField::Publisher),
+            "developer" | "dev" => Key::Field(SearchField::Developer),
+            "console" | "platform" | "system" => Key::Field(SearchField::Console),
+            "collection" => Key::Field(SearchField::Collection),
+            "category" => Key::Field(SearchField::Category),
+            "group" | "pack" => Key::Field(SearchField::Group),
+            "variant" => Key::Field(SearchField::Variant),
+            "manufacturer" | "maker" => Key::Field(SearchField::Manufacturer),
+            "year" => Key::Year,
+            "tag" => Key::Tag,
+            "region" => Key::Region,
+            "is" => Key::Is,
+            _ => return None,
+        };
+        Some((key, name))
+    }
+
+    /// A quoted phrase or a run of non-whitespace characters.
+    fn value(&mut self) -> Result<(String, bool), QuerySyntaxError> {
+        if self.peek() == Some('"') {
+            let open = self.pos;
+            self.pos += 1;
+            let start = self.pos;
+            while self.peek().is_some_and(|c| c != '"') {
+                self.pos += 1;
+            }
+            if self.peek().is_none() {
+                return Err(error(open, self.pos, "unterminated quote"));
+            }
+            let value = self.chars[start..self.pos].iter().collect();
+            self.pos += 1;
+            return Ok((value, true));
+        }
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !c.is_whitespace()) {
+            self.pos += 1;
+        }
+        Ok((self.chars[start..self.pos].iter().collect(), false))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+}
+
+fn key_term(
+    key: Key,
+    value: &str,
+    start: usize,
+    end: usize,
+) -> Result<SearchTerm, QuerySyntaxError> {
+    let value = value.trim();
+    match key {
+        Key::Field(field) => Ok(SearchTerm::Field(field, normalize_for_search(value))),
+        Key::Tag => Ok(SearchTerm::Tag(normalize_for_search(value))),
+        Key::Year => parse_year_range(value)
+            .ok_or_else(|| error(start, end, "expected a year or range like 1990..1995")),
+        Key::Region => parse_region(value).map(SearchTerm::Region).ok_or_else(|| {
+            error(start, end, "expected a region: jp, na, pal, eu, au or br")
+        }),
+        Key::Is => status_flag(value).map(SearchTerm::Status).ok_or_else(|| {
+            error(
+                start,
+                end,
+                "expected owned, favorite, wishlist, backlog, playing, beaten, completed or abandoned",
+            )
+        }),
+    }
+}
+
+fn parse_year_range(value: &str) -> Option<SearchTerm> {
+    let year = |s: &str| -> Option<Option<u32>> {
+        if s.is_empty() {
+            Some(None)
+        } else {
+            s.parse::<u32>().ok().map(Some)
+        }
+    };
+    let (min, max) = match value.split_once("..") {
+        Some((min, max)) => (year(min)?, year(max)?),
+        None => {
+            let exact = value.parse::<u32>().ok()?;
+            (Some(exact), Some(exact))
+        }
+    };
+    match (min, max) {
+        (None, None) => None,
+        (Some(min), Some(max)) if min > max => None,
+        _ => Some(SearchTerm::Year { min, max }),
+    }
+}
+
+fn parse_region(value: &str) -> Option<Region> {
+    match value.to_ascii_lowercase().as_str() {
+        "jp" | "japan" => Some(Region::Jp),
+        "na" | "us" | "ntsc-u" => Some(Region::Na),
+        "pal" => Some(Region::Pal),
+        "eu" | "europe" => Some(Region::Eu),
+        "au" | "australia" => Some(Region::Au),
+        "br" | "brazil" => Some(Region::Br),
+        _ => None,
+    }
+}
+
+fn status_flag(value: &str) -> Option<StatusFlag> {
+    match value.to_ascii_lowercase().as_str() {
+        "owned" => Some(StatusFlag::Owned),
+        "favorite" | "favorites" | "fav" => Some(StatusFlag::Favorite),
+        "wishlist" => Some(StatusFlag::Wishlist),
+        "backlog" => Some(StatusFlag::Play(PlayStatus::Backlog)),
+        "playing" => Some(StatusFlag::Play(PlayStatus::Playing)),
+        "beaten" => Some(StatusFlag::Play(PlayStatus::Beaten)),
+        "completed" => Some(StatusFlag::Play(PlayStatus::Completed)),
+        "abandoned" => Some(StatusFlag::Play(PlayStatus::Abandoned)),
+        _ => None,
+    }
+}
+
+fn error(start: usize, end: usize, message: impl Into<String>) -> QuerySyntaxError {
+    QuerySyntaxError {
+        start,
+        end,
+        message: message.into(),
     }
 }
 
@@ -72,6 +499,26 @@ pub(crate) fn matches_filter(state: &EntryState, filter: FilterBy) -> bool {
         FilterBy::Favorites => state.favorite,
         FilterBy::Wishlist => state.wishlist,
         FilterBy::NotOwned => !state.owned,
+        FilterBy::Backlog => state.play_status == Some(PlayStatus::Backlog),
+        FilterBy::Playing => state.play_status == Some(PlayStatus::Playing),
+        FilterBy::Beaten => state.play_status == Some(PlayStatus::Beaten),
+        FilterBy::Completed => state.play_status == Some(PlayStatus::Completed),
+        FilterBy::Abandoned => state.play_status == Some(PlayStatus::Abandoned),
+    }
+}
+
+pub(crate) fn matches_tags(state: &EntryState, tags: &[String]) -> bool {
+    tags.iter()
+        .all(|tag| state.tags.iter().any(|t| t == tag.trim()))
+}
+
+/// Orders play statuses in declaration order with unset statuses last.
+pub(crate) fn cmp_play_status(a: &EntryState, b: &EntryState) -> std::cmp::Ordering {
+    match (a.play_status, b.play_status) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
     }
 }
 
@@ -87,6 +534,16 @@ pub(crate) fn status_score(state: &EntryState) -> u8 {
     }
 }
 
+/// Orders release dates ascending with undated entries last.
+pub(crate) fn cmp_release_date(a: Option<&str>, b: Option<&str>) -> std::cmp::Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    }
+}
+
 pub(crate) fn paginate<T>(
     items: Vec<T>,
     offset: Option<usize>,
@@ -96,5 +553,113 @@ pub(crate) fn paginate<T>(
     let offset = offset.unwrap_or(0).min(total);
     let limit = limit.unwrap_or(total - offset);
     let items = items.into_iter().skip(offset).take(limit).collect();
-    QueryResult { total, items }
+    QueryResult {
+        total,
+        items,
+        error: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<SearchQuery, QuerySyntaxError> {
+        parse_search(Some(input))
+    }
+
+    fn clause(negated: bool, term: SearchTerm) -> SearchClause {
+        SearchClause { negated, term }
+    }
+
+    #[test]
+    fn parses_fields_ranges_flags_and_phrases() {
+        let query =
+            parse("publisher:capcom year:1990..1995 console:snes -owned tag:rare \"mega man\"")
+                .expect("valid query");
+        assert_eq!(
+            query.clauses,
+            vec![
+                clause(
+                    false,
+                    SearchTerm::Field(SearchField::Publisher, "capcom".to_string())
+                ),
+                clause(
+                    false,
+                    SearchTerm::Year {
+                        min: Some(1990),
+                        max: Some(1995)
+                    }
+                ),
+                clause(
+                    false,
+                    SearchTerm::Field(SearchField::Console, "snes".to_string())
+                ),
+                clause(true, SearchTerm::Status(StatusFlag::Owned)),
+                clause(false, SearchTerm::Tag("rare".to_string())),
+                clause(false, SearchTerm::Text("mega man".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn open_ranges_and_quoted_field_values() {
+        let query = parse("year:..1989 title:\"final fantasy\" is:backlog").unwrap();
+        assert_eq!(
+            query.clauses,
+            vec![
+                clause(
+                    false,
+                    SearchTerm::Year {
+                        min: None,
+                        max: Some(1989)
+                    }
+                ),
+                clause(
+                    false,
+                    SearchTerm::Field(SearchField::Title, "final fantasy".to_string())
+                ),
+                clause(
+                    false,
+                    SearchTerm::Status(StatusFlag::Play(PlayStatus::Backlog))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_prefixes_and_punctuation_stay_plain_text() {
+        let query = parse("Zelda: Ocarina \"owned\" :").unwrap();
+        assert_eq!(
+            query.clauses,
+            vec![
+                clause(false, SearchTerm::Text("zelda".to_string())),
+                clause(false, SearchTerm::Text("ocarina".to_string())),
+                clause(false, SearchTerm::Text("owned".to_string())),
+            ]
+        );
+        assert!(parse("   ").unwrap().clauses.is_empty());
+        assert!(parse_search(None).unwrap().clauses.is_empty());
+    }
+
+    #[test]
+    fn errors_report_character_positions() {
+        let err = parse("mario year:199x").unwrap_err();
+        assert_eq!((err.start, err.end), (11, 15));
+
+        let err = parse("year:1995..1990").unwrap_err();
+        assert_eq!((err.start, err.end), (5, 15));
+
+        let err = parse("pokémon publisher:").unwrap_err();
+        assert_eq!((err.start, err.end), (8, 18));
+
+        let err = parse("is:sold").unwrap_err();
+        assert_eq!((err.start, err.end), (3, 7));
+
+        let err = parse("snes - owned").unwrap_err();
+        assert_eq!((err.start, err.end), (5, 6));
+
+        let err = parse("title:\"never closed").unwrap_err();
+        assert_eq!((err.start, err.end), (6, 19));
+    }
 }
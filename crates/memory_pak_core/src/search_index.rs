@@ -0,0 +1,211 @@
+//! Candidate lookup for game searches, backed by the word index `build.rs`
+//! compiles into the catalog blob.
+//!
+//! The index only narrows the rows a query has to score; every candidate is
+//! still checked by `SearchQuery::score`, so a lookup may over-approximate
+//! but must never drop a row the full scan would keep.
+
+use std::collections::HashMap;
+
+use crate::compiled::CompiledSearchIndex;
+use crate::fuzzy::text_score;
+use crate::ids::{normalize_for_search, EntryId};
+use crate::model::Console;
+use crate::query::{SearchField, SearchQuery, SearchTerm};
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SearchIndex {
+    /// Game ids in compiled order; postings are positions in this list.
+    games: Vec<EntryId>,
+    words: Vec<(String, Vec<u32>)>,
+    console_games: HashMap<EntryId, Vec<u32>>,
+}
+
+impl SearchIndex {
+    /// `games` pairs each game id with its console id, in compiled order.
+    pub(crate) fn new(games: Vec<(EntryId, EntryId)>, compiled: CompiledSearchIndex) -> Self {
+        let mut console_games: HashMap<EntryId, Vec<u32>> = HashMap::new();
+        let games = games
+            .into_iter()
+            .enumerate()
+            .map(|(position, (id, console_id))| {
+                let position = u32::try_from(position).expect("game count fits in u32");
+                console_games.entry(console_id).or_default().push(position);
+                id
+            })
+            .collect();
+
+        let words = compiled
+            .words
+            .into_iter()
+            .map(|posting| {
+                let mut position = 0;
+                let games = posting
+                    .game_gaps
+                    .into_iter()
+                    .map(|gap| {
+                        position += gap;
+                        position
+                    })
+                    .collect();
+                (posting.word, games)
+            })
+            .collect();
+
+        Self {
+            games,
+            words,
+            console_games,
+        }
+    }
+
+    /// Ids of the games that can satisfy every positive text clause of
+    /// `query`, or `None` when no clause narrows the search.
+    pub(crate) fn game_candidates(
+        &self,
+        query: &SearchQuery,
+        consoles: &[Console],
+    ) -> Option<Vec<&EntryId>> {
+        let mut keep: Option<Vec<bool>> = None;
+        for clause in query.clauses.iter().filter(|clause| !clause.negated) {
+            let matched = match &clause.term {
+                SearchTerm::Text(needle) => self.clause_matches(None, needle, true, consoles),
+                SearchTerm::Field(field, needle) => {
+                    self.clause_matches(Some(*field), needle, false, consoles)
+                }
+                _ => None,
+            };
+            let Some(matched) = matched else {
+                continue;
+            };
+            keep = Some(match keep {
+                None => matched,
+                Some(mut keep) => {
+                    for (kept, hit) in keep.iter_mut().zip(matched) {
+                        *kept &= hit;
+                    }
+                    keep
+                }
+            });
+        }
+
+        let keep = keep?;
+        Some(
+            self.games
+                .iter()
+                .zip(keep)
+                .filter_map(|(id, kept)| kept.then_some(id))
+                .collect(),
+        )
+    }
+
+    /// Marks the games one text clause can match, mirroring the fields
+    /// `GameRow` exposes to search. Returns `None` for an empty needle,
+    /// which matches everything.
+    fn clause_matches(
+        &self,
+        field: Option<SearchField>,
+        needle: &str,
+        typos: bool,
+        consoles: &[Console],
+    ) -> Option<Vec<bool>> {
+        // A phrase only matches as a substring, so its longest word has to
+        // sit inside a single indexed word. Typos never apply to phrases.
+        let piece = needle.split_whitespace().max_by_key(|piece| piece.len())?;
+        let phrase = piece.len() != needle.len();
+        let mut matched = vec![false; self.games.len()];
+        let mut mark = |positions: &[u32]| {
+            for &position in positions {
+                matched[position as usize] = true;
+            }
+        };
+
+        if matches!(
+            field,
+            None | Some(SearchField::Title | SearchField::Publisher | SearchField::Developer)
+        ) {
+            for (word, games) in &self.words {
+                let hit = if phrase {
+                    word.contains(piece)
+                } else {
+                    text_score(word, needle, false, typos).is_some()
+                };
+                if hit {
+                    mark(games);
+                }
+            }
+        }
+
+        for console in consoles {
+            let texts: &[&str] = match field {
+                None => &[&console.name],
+                Some(SearchField::Console) => {
+                    &[&console.name, &console.short_id, &console.abbreviation]
+                }
+                Some(SearchField::Manufacturer) => &[&console.manufacturer],
+                Some(_) => &[],
+            };
+            let hit = texts.iter().any(|text| {
+                text_score(&normalize_for_search(text), needle, false, typos).is_some()
+            });
+            if hit {
+                if let Some(games) = self.console_games.get(&console.id) {
+                    mark(games);
+                }
+            }
+        }
+
+        Some(matched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::catalog::catalog;
+    use crate::ids::normalize_for_search;
+    use crate::query::parse_search;
+
+    fn candidates(search: &str) -> Option<Vec<String>> {
+        let catalog = catalog();
+        let query = parse_search(Some(search)).expect("valid search");
+        catalog
+            .search
+            .game_candidates(&query, &catalog.consoles)
+            .map(|ids| ids.into_iter().map(|id| id.as_str().to_string()).collect())
+    }
+
+    #[test]
+    fn candidates_cover_every_matching_game() {
+        let games = &catalog().games;
+        let typo = candidates("castelvania").expect("text narrows the search");
+        assert!(typo.len() < games.len());
+        for game in games.values() {
+            if normalize_for_search(&game.title).contains("castlevania") {
+                assert!(typo.iter().any(|id| id == game.id.as_str()), "{}", game.id);
+            }
+        }
+
+        let phrase = candidates("\"street fighter\"").expect("phrase narrows the search");
+        for game in games.values() {
+            if normalize_for_search(&game.title).contains("street fighter") {
+                assert!(
+                    phrase.iter().any(|id| id == game.id.as_str()),
+                    "{}",
+                    game.id
+                );
+            }
+        }
+
+        let console = candidates("console:snes").expect("console narrows the search");
+        assert!(games
+            .values()
+            .filter(|game| game.console_id.as_str() == "console:snes")
+            .all(|game| console.iter().any(|id| id == game.id.as_str())));
+    }
+
+    #[test]
+    fn non_text_clauses_do_not_narrow() {
+        assert_eq!(candidates("year:1990..1995 -mario owned"), None);
+        assert_eq!(candidates(""), None);
+    }
+}
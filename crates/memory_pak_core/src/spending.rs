@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use chrono::Datelike;
+
+use crate::ids::{EntryId, EntryKind};
+use crate::model::{
+    Catalog, OwnedCopy, PersistedState, SpendGroup, SpendTotal, SpendingReport, YearSpend,
+};
+
+/// Running sums keyed by currency code.
+#[derive(Default)]
+pub(crate) struct Totals(BTreeMap<String, (u64, usize)>);
+
+impl Totals {
+    pub(crate) fn add(&mut self, currency: &str, amount: u64) {
+        let entry = self.0.entry(currency.to_string()).or_default();
+        entry.0 = entry.0.saturating_add(amount);
+        entry.1 += 1;
+    }
+
+    pub(crate) fn into_vec(self) -> Vec<SpendTotal> {
+        self.0
+            .into_iter()
+            .map(|(currency, (amount, copies))| SpendTotal {
+                currency,
+                amount,
+                copies,
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn spending_report(catalog: &Catalog, state: &PersistedState) -> SpendingReport {
+    let mut totals = Totals::default();
+    let mut by_console: BTreeMap<EntryId, Totals> = BTreeMap::new();
+    let mut by_collection: BTreeMap<String, Totals> = BTreeMap::new();
+    let mut by_year: BTreeMap<i32, Totals> = BTreeMap::new();
+
+    for (id, entry) in &state.entries {
+        let (console, collection) = owner_of(catalog, id);
+        for copy in &entry.copies {
+            let Some(amount) = copy.purchase_price else {
+                continue;
+            };
+            let currency = currency_of(copy);
+            totals.add(&currency, amount);
+            if let Some(console) = &console {
+                by_console
+                    .entry(console.clone())
+                    .or_default()
+                    .add(&currency, amount);
+            }
+            if let Some(collection) = &collection {
+                by_collection
+                    .entry(collection.clone())
+                    .or_default()
+                    .add(&currency, amount);
+            }
+            if let Some(date) = copy.acquired_on {
+                by_year
+                    .entry(date.year())
+                    .or_default()
+                    .add(&currency, amount);
+            }
+        }
+    }
+
+    SpendingReport {
+        totals: totals.into_vec(),
+        by_console: by_console
+            .into_iter()
+            .map(|(id, totals)| SpendGroup {
+                name: console_name(catalog, &id),
+                id: id.into_string(),
+                totals: totals.into_vec(),
+            })
+            .collect(),
+        by_collection: by_collection
+            .into_iter()
+            .map(|(id, totals)| SpendGroup {
+                name: collection_name(catalog, &id),
+                id,
+                totals: totals.into_vec(),
+            })
+            .collect(),
+        by_year: by_year
+            .into_iter()
+            .map(|(year, totals)| YearSpend {
+                year,
+                totals: totals.into_vec(),
+            })
+            .collect(),
+    }
+}
+
+pub(crate) fn console_name(catalog: &Catalog, id: &EntryId) -> String {
+    catalog
+        .console(id)
+        .map(|console| console.name.clone())
+        .unwrap_or_else(|| id.locator().to_string())
+}
+
+pub(crate) fn collection_name(catalog: &Catalog, id: &str) -> String {
+    catalog
+        .collections
+        .iter()
+        .find(|c| c.id == id)
+        .map(|c| c.name.clone())
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// The copy's currency code, trimmed and uppercased so `usd` and `USD`
+/// share a total.
+pub(crate) fn currency_of(copy: &OwnedCopy) -> String {
+    copy.currency
+        .as_deref()
+        .map(|code| code.trim().to_ascii_uppercase())
+        .unwrap_or_default()
+}
+
+/// The console and collection an entry's spend rolls up into.
+pub(crate) fn owner_of(catalog: &Catalog, id: &EntryId) -> (Option<EntryId>, Option<String>) {
+    match id.kind() {
+        Some(EntryKind::Console) => (Some(id.clone()), None),
+        Some(EntryKind::Game) => (catalog.games.get(id).map(|g| g.console_id.clone()), None),
+        Some(EntryKind::Collectible) => (
+            None,
+            catalog.collectible(id).map(|c| c.collection_id.clone()),
+        ),
+        None => (None, None),
+    }
+}
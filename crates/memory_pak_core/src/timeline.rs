@@ -0,0 +1,344 @@
+//! Owned counts over time, for growth charts and monthly reports.
+//!
+//! A copy is dated by its `acquired_on`. Copies without one take the day
+//! the entry was last marked owned in the activity log; copies the log
+//! does not cover either are only counted in `undated_copies`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, Days, Months, NaiveDate, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::activity::ActivityChange;
+use crate::app::CoreError;
+use crate::ids::EntryId;
+use crate::model::{Catalog, OwnedCopy, PersistedState, SpendTotal};
+use crate::spending::{collection_name, console_name, currency_of, owner_of, Totals};
+
+/// Most buckets one report may have: a century of weeks.
+pub const MAX_TIMELINE_BUCKETS: usize = 5_218;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeBucket {
+    /// ISO weeks, starting on Monday.
+    Week,
+    #[default]
+    Month,
+    Year,
+}
+
+impl TimeBucket {
+    /// First day of the bucket holding `date`.
+    pub fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            TimeBucket::Week => date.week(Weekday::Mon).first_day(),
+            TimeBucket::Month => date.with_day(1).expect("every month has a day 1"),
+            TimeBucket::Year => date.with_ordinal(1).expect("every year has a day 1"),
+        }
+    }
+
+    fn next(self, start: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            TimeBucket::Week => start.checked_add_days(Days::new(7)),
+            TimeBucket::Month => start.checked_add_months(Months::new(1)),
+            TimeBucket::Year => start.checked_add_months(Months::new(12)),
+        }
+    }
+}
+
+/// The range is widened to whole buckets and may hold at most
+/// [`MAX_TIMELINE_BUCKETS`] of them.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimelineInput {
+    pub bucket: TimeBucket,
+    /// Defaults to the earliest acquisition, but no earlier than the year of
+    /// the catalog's first release, so a mistyped year cannot stretch the
+    /// range. Copies from before it still count towards the running totals.
+    pub from: Option<NaiveDate>,
+    /// Defaults to today. Copies from after it are left out.
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelinePoint {
+    /// Copies acquired during the bucket.
+    pub acquired: usize,
+    /// Entries owned by the end of the bucket, each dated by its first copy.
+    pub owned: usize,
+    /// Copies owned by the end of the bucket.
+    pub owned_copies: usize,
+    /// What the copies acquired during the bucket cost, per currency.
+    pub spent: Vec<SpendTotal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineSeries {
+    /// Console `EntryId` or collection id.
+    pub id: String,
+    pub name: String,
+    pub points: Vec<TimelinePoint>,
+}
+
+/// Every `points` list has one point per entry of `buckets`. Consoles and
+/// games roll up into `by_console` and collectibles into `by_collection`,
+/// as in the spending report.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineReport {
+    pub bucket: TimeBucket,
+    /// First day of each bucket, oldest first.
+    pub buckets: Vec<NaiveDate>,
+    pub total: Vec<TimelinePoint>,
+    pub by_console: Vec<TimelineSeries>,
+    pub by_collection: Vec<TimelineSeries>,
+    /// Owned copies with no date, which no series includes.
+    pub undated_copies: usize,
+}
+
+/// Where a date falls relative to the report's buckets.
+#[derive(Clone, Copy)]
+enum Slot {
+    Before,
+    In(usize),
+}
+
+/// One series while it is being counted.
+struct Tally {
+    /// Entries and copies from before the first bucket.
+    carried_entries: usize,
+    carried_copies: usize,
+    entries: Vec<usize>,
+    copies: Vec<usize>,
+    spent: Vec<Totals>,
+}
+
+impl Tally {
+    fn new(buckets: usize) -> Self {
+        let mut spent = Vec::with_capacity(buckets);
+        spent.resize_with(buckets, Totals::default);
+        Self {
+            carried_entries: 0,
+            carried_copies: 0,
+            entries: vec![0; buckets],
+            copies: vec![0; buckets],
+            spent,
+        }
+    }
+
+    fn add_entry(&mut self, slot: Slot) {
+        match slot {
+            Slot::Before => self.carried_entries += 1,
+            Slot::In(i) => self.entries[i] += 1,
+        }
+    }
+
+    fn add_copy(&mut self, slot: Slot, copy: &OwnedCopy) {
+        match slot {
+            Slot::Before => self.carried_copies += 1,
+            Slot::In(i) => {
+                self.copies[i] += 1;
+                if let Some(amount) = copy.purchase_price {
+                    self.spent[i].add(&currency_of(copy), amount);
+                }
+            }
+        }
+    }
+
+    fn into_points(self) -> Vec<TimelinePoint> {
+        let mut owned = self.carried_entries;
+        let mut owned_copies = self.carried_copies;
+        self.entries
+            .into_iter()
+            .zip(self.copies)
+            .zip(self.spent)
+            .map(|((entries, copies), spent)| {
+                owned += entries;
+                owned_copies += copies;
+                TimelinePoint {
+                    acquired: copies,
+                    owned,
+                    owned_copies,
+                    spent: spent.into_vec(),
+                }
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn growth_timeline(
+    catalog: &Catalog,
+    state: &PersistedState,
+    input: TimelineInput,
+) -> Result<TimelineReport, CoreError> {
+    // Events are oldest first, so the last one wins.
+    let mut marked_owned: HashMap<&EntryId, NaiveDate> = HashMap::new();
+    for event in &state.activity.events {
+        if let ActivityChange::Owned {
+            old: false,
+            new: true,
+        } = event.change
+        {
+            marked_owned.insert(&event.id, event.at.date_naive());
+        }
+    }
+
+    let mut undated_copies = 0;
+    let mut dated: Vec<(&EntryId, Vec<(NaiveDate, &OwnedCopy)>)> = Vec::new();
+    for (id, entry) in &state.entries {
+        // Orphans are left out, as they are from the collection stats.
+        if catalog.get(id).is_none() {
+            continue;
+        }
+        let mut copies = Vec::with_capacity(entry.copies.len());
+        for copy in &entry.copies {
+            match copy.acquired_on.or_else(|| marked_owned.get(id).copied()) {
+                Some(date) => copies.push((date, copy)),
+                None => undated_copies += 1,
+            }
+        }
+        if !copies.is_empty() {
+            dated.push((id, copies));
+        }
+    }
+
+    let to = input.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = input.from.or_else(|| {
+        let earliest = dated
+            .iter()
+            .flat_map(|(_, copies)| copies.iter().map(|(date, _)| *date))
+            .min()?;
+        Some(first_release(catalog).map_or(earliest, |first| earliest.max(first)))
+    });
+    let buckets = match from {
+        Some(from) => bucket_starts(input.bucket, from, to)
+            .ok_or(CoreError::TimelineTooLong(MAX_TIMELINE_BUCKETS))?,
+        None => Vec::new(),
+    };
+    let slot = |date: NaiveDate| match buckets.binary_search(&input.bucket.start_of(date)) {
+        Ok(i) => Some(Slot::In(i)),
+        Err(0) => Some(Slot::Before),
+        Err(_) => None,
+    };
+
+    let mut total = Tally::new(buckets.len());
+    let mut by_console: BTreeMap<EntryId, Tally> = BTreeMap::new();
+    let mut by_collection: BTreeMap<String, Tally> = BTreeMap::new();
+    for (id, copies) in dated {
+        let first = copies.iter().map(|(date, _)| *date).min();
+        let Some(entry_slot) = first.and_then(slot) else {
+            continue;
+        };
+        let (console, collection) = owner_of(catalog, id);
+        let mut tallies = vec![&mut total];
+        if let Some(console) = console {
+            tallies.push(
+                by_console
+                    .entry(console)
+                    .or_insert_with(|| Tally::new(buckets.len())),
+            );
+        }
+        if let Some(collection) = collection {
+            tallies.push(
+                by_collection
+                    .entry(collection)
+                    .or_insert_with(|| Tally::new(buckets.len())),
+            );
+        }
+        for tally in tallies {
+            tally.add_entry(entry_slot);
+            for (date, copy) in &copies {
+                if let Some(slot) = slot(*date) {
+                    tally.add_copy(slot, copy);
+                }
+            }
+        }
+    }
+
+    Ok(TimelineReport {
+        bucket: input.bucket,
+        total: total.into_points(),
+        by_console: by_console
+            .into_iter()
+            .map(|(id, tally)| TimelineSeries {
+                name: console_name(catalog, &id),
+                id: id.into_string(),
+                points: tally.into_points(),
+            })
+            .collect(),
+        by_collection: by_collection
+            .into_iter()
+            .map(|(id, tally)| TimelineSeries {
+                name: collection_name(catalog, &id),
+                id,
+                points: tally.into_points(),
+            })
+            .collect(),
+        buckets,
+        undated_copies,
+    })
+}
+
+/// January 1st of the year the oldest catalog game came out.
+fn first_release(catalog: &Catalog) -> Option<NaiveDate> {
+    let year = catalog
+        .games
+        .values()
+        .map(|game| game.year)
+        .filter(|&year| year > 0)
+        .min()?;
+    NaiveDate::from_ymd_opt(i32::try_from(year).ok()?, 1, 1)
+}
+
+/// Starts of every bucket from the one holding `from` to the one holding
+/// `to`, or `None` past [`MAX_TIMELINE_BUCKETS`].
+fn bucket_starts(bucket: TimeBucket, from: NaiveDate, to: NaiveDate) -> Option<Vec<NaiveDate>> {
+    let last = bucket.start_of(to);
+    let mut starts = Vec::new();
+    let mut start = Some(bucket.start_of(from));
+    while let Some(current) = start.filter(|start| *start <= last) {
+        if starts.len() == MAX_TIMELINE_BUCKETS {
+            return None;
+        }
+        starts.push(current);
+        start = bucket.next(current);
+    }
+    Some(starts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn buckets_cover_whole_weeks_months_and_years() {
+        // 2024-01-03 is a Wednesday.
+        assert_eq!(
+            bucket_starts(TimeBucket::Week, date(2024, 1, 3), date(2024, 1, 15)),
+            Some(vec![date(2024, 1, 1), date(2024, 1, 8), date(2024, 1, 15)])
+        );
+        assert_eq!(
+            bucket_starts(TimeBucket::Month, date(2023, 11, 30), date(2024, 1, 1)),
+            Some(vec![date(2023, 11, 1), date(2023, 12, 1), date(2024, 1, 1)])
+        );
+        assert_eq!(
+            bucket_starts(TimeBucket::Year, date(2023, 6, 1), date(2024, 2, 29)),
+            Some(vec![date(2023, 1, 1), date(2024, 1, 1)])
+        );
+        assert_eq!(
+            bucket_starts(TimeBucket::Month, date(2024, 2, 1), date(2024, 1, 1)),
+            Some(Vec::new())
+        );
+        assert_eq!(
+            bucket_starts(TimeBucket::Week, date(202, 5, 1), date(2024, 1, 1)),
+            None
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! Routing from REST requests to `MemoryPakApp` calls. Bodies and responses
+//! are the core's serde types, so they match what the app's backends send.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use memory_pak_core::{
+    load_persisted_state_from, save_persisted_state_to, CoreError, CsvImportOptions, EntryId,
+    ImportOptions, MemoryPakApp, QueryInput, SetItemNotesInput, SetItemStatusInput,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// The collection behind the API, kept in step with `state.json`.
+pub struct Store {
+    path: PathBuf,
+    app: MemoryPakApp,
+    /// Modification time of the file as last read or written. Another writer,
+    /// such as the desktop app or the CLI, changes it and triggers a reload.
+    modified: Option<SystemTime>,
+}
+
+impl Store {
+    pub fn open(path: PathBuf) -> io::Result<Self> {
+        let state = load_persisted_state_from(&path)?;
+        Ok(Self {
+            modified: modified(&path),
+            app: MemoryPakApp::from_persisted_state(state),
+            path,
+        })
+    }
+
+    fn refresh(&mut self) -> io::Result<()> {
+        let current = modified(&self.path);
+        if current != self.modified {
+            self.app = MemoryPakApp::from_persisted_state(load_persisted_state_from(&self.path)?);
+            self.modified = current;
+        }
+        Ok(())
+    }
+
+    fn save(&mut self) -> io::Result<()> {
+        save_persisted_state_to(&self.path, self.app.persisted_state())?;
+        self.modified = modified(&self.path);
+        Ok(())
+    }
+}
+
+fn modified(path: &std::path::Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+pub struct Request<'a> {
+    pub method: &'a str,
+    /// Path and optional query string, as sent.
+    pub url: &'a str,
+    /// The `Authorization` header.
+    pub authorization: Option<&'a str>,
+    pub body: &'a str,
+}
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    fn json(status: u16, value: &impl Serialize) -> Self {
+        match serde_json::to_string(value) {
+            Ok(body) => Self {
+                status,
+                content_type: "application/json",
+                body,
+            },
+            Err(err) => Self::error(500, err),
+        }
+    }
+
+    fn text(content_type: &'static str, body: String) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body,
+        }
+    }
+
+    pub fn error(status: u16, message: impl ToString) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: json!({ "error": message.to_string() }).to_string(),
+        }
+    }
+}
+
+impl From<CoreError> for Response {
+    fn from(err: CoreError) -> Self {
+        let status = match err {
+            CoreError::UnknownEntry(_) | CoreError::UnknownList(_) => 404,
+            _ => 400,
+        };
+        Self::error(status, err)
+    }
+}
+
+impl From<io::Error> for Response {
+    fn from(err: io::Error) -> Self {
+        Self::error(500, err)
+    }
+}
+
+/// Answers one request. Every route needs `Authorization: Bearer <token>`.
+pub fn handle(store: &mut Store, token: &str, request: &Request<'_>) -> Response {
+    if let Err(response) = authorize(token, request.authorization) {
+        return response;
+    }
+    if let Err(err) = store.refresh() {
+        return err.into();
+    }
+    route(store, request).unwrap_or_else(|response| response)
+}
+
+/// The 401 for a missing or wrong token. The server checks this before it
+/// reads the body, so no upload is buffered for an unknown client.
+pub fn authorize(token: &str, header: Option<&str>) -> Result<(), Response> {
+    if authorized(token, header) {
+        Ok(())
+    } else {
+        Err(Response::error(401, "missing or invalid bearer token"))
+    }
+}
+
+fn authorized(token: &str, header: Option<&str>) -> bool {
+    let Some(given) = header.and_then(|h| h.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    // Compare every byte so the time taken does not leak a matching prefix.
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn route(store: &mut Store, request: &Request<'_>) -> Result<Response, Response> {
+    let (path, query) = request.url.split_once('?').unwrap_or((request.url, ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let app = &mut store.app;
+
+    let response = match (request.method, segments.as_slice()) {
+        ("POST", ["api", "games", "query"]) => {
+            Response::json(200, &app.query_games(body_or_default(request)?))
+        }
+        ("POST", ["api", "consoles", "query"]) => {
+            Response::json(200, &app.query_consoles(body_or_default(request)?))
+        }
+        ("POST", ["api", "collectibles", "query"]) => {
+            Response::json(200, &app.query_collectibles(body_or_default(request)?))
+        }
+        // Ids contain a `/`, so they span the rest of the path.
+        ("GET", ["api", "items", id @ ..]) if !id.is_empty() => {
+            let id = EntryId::from_raw(percent_decode(&id.join("/")));
+            Response::json(200, &app.get_item(&id)?)
+        }
+        ("POST", ["api", "items", "status"]) => {
+            let result = app.set_item_status(body::<SetItemStatusInput>(request)?)?;
+            store.save()?;
+            Response::json(200, &result)
+        }
+        ("POST", ["api", "items", "notes"]) => {
+            let result = app.set_item_notes(body::<SetItemNotesInput>(request)?)?;
+            store.save()?;
+            Response::json(200, &result)
+        }
+        ("GET", ["api", "stats"]) => Response::json(200, &app.collection_stats()),
+        ("POST", ["api", "import", "json"]) => {
+            let summary = app.import_json(request.body, import_options(query)?)?;
+            store.save()?;
+            Response::json(200, &summary)
+        }
+        ("POST", ["api", "import", "csv"]) => {
+            let report = app.import_csv(request.body, CsvImportOptions::default())?;
+            store.save()?;
+            Response::json(200, &report)
+        }
+        ("GET", ["api", "export", "json"]) => Response::text(
+            "application/json",
+            app.export_json().map_err(|err| Response::error(500, err))?,
+        ),
+        ("GET", ["api", "export", "csv"]) => Response::text("text/csv", app.export_csv()),
+        _ => Response::error(404, format!("no route for {} {path}", request.method)),
+    };
+    Ok(response)
+}
+
+fn body<T: DeserializeOwned>(request: &Request<'_>) -> Result<T, Response> {
+    serde_json::from_str(request.body).map_err(|err| Response::error(400, err))
+}
+
+/// Query routes accept an empty body as the default `QueryInput`.
+fn body_or_default(request: &Request<'_>) -> Result<QueryInput, Response> {
+    if request.body.trim().is_empty() {
+        Ok(QueryInput::default())
+    } else {
+        body(request)
+    }
+}
+
+/// `?mode=replace&strategy=keepLocal`, using the serde names of
+/// `ImportMode` and `ImportStrategy`.
+fn import_options(query: &str) -> Result<ImportOptions, Response> {
+    let mut options = serde_json::Map::new();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        options.insert(percent_decode(key), Value::String(percent_decode(value)));
+    }
+    serde_json::from_value(Value::Object(options)).map_err(|err| Response::error(400, err))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use memory_pak_core::{CollectionStats, MutationResult};
+
+    const TOKEN: &str = "secret";
+
+    fn request<'a>(method: &'a str, url: &'a str, body: &'a str) -> Request<'a> {
+        Request {
+            method,
+            url,
+            authorization: Some("Bearer secret"),
+            body,
+        }
+    }
+
+    fn temp_store(name: &str) -> (Store, PathBuf) {
+        let dir =
+            std::env::temp_dir().join(format!("memory-pak-server-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        let _ = fs::remove_file(&path);
+        (Store::open(path).unwrap(), dir)
+    }
+
+    #[test]
+    fn requests_without_the_token_are_rejected() {
+        let (mut store, dir) = temp_store("auth");
+        let mut req = request("GET", "/api/stats", "");
+        req.authorization = Some("Bearer wrong!");
+        assert_eq!(handle(&mut store, TOKEN, &req).status, 401);
+        req.authorization = None;
+        assert_eq!(handle(&mut store, TOKEN, &req).status, 401);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn status_changes_are_saved_and_reflected_in_stats() {
+        let (mut store, dir) = temp_store("status");
+        let body = r#"{"id":"game:nes/super-mario-bros","owned":true}"#;
+
+        let response = handle(
+            &mut store,
+            TOKEN,
+            &request("POST", "/api/items/status", body),
+        );
+        assert_eq!(response.status, 200, "{}", response.body);
+        let result: MutationResult = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(result.stats.owned_games, 1);
+
+        let saved = load_persisted_state_from(&store.path).unwrap();
+        assert!(saved.entries[&result.id].owned);
+
+        let stats = handle(&mut store, TOKEN, &request("GET", "/api/stats", ""));
+        let stats: CollectionStats = serde_json::from_str(&stats.body).unwrap();
+        assert_eq!(stats.owned_games, 1);
+
+        let item = "/api/items/game%3Anes/super-mario-bros";
+        assert_eq!(
+            handle(&mut store, TOKEN, &request("GET", item, "")).status,
+            200
+        );
+        let missing = handle(
+            &mut store,
+            TOKEN,
+            &request("GET", "/api/items/game:nes/nope", ""),
+        );
+        assert_eq!(missing.status, 404);
+        let bad = handle(
+            &mut store,
+            TOKEN,
+            &request("POST", "/api/import/json?mode=sideways", "{}"),
+        );
+        assert_eq!(bad.status, 400);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
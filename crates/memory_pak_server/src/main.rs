@@ -0,0 +1,135 @@
+//! `memory-pak-server`: a small HTTP/JSON API over the desktop app's
+//! `state.json`, for scripts and devices on the local network.
+
+mod api;
+
+use std::io::Read;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use memory_pak_core::default_state_path;
+use tiny_http::{Header, Server};
+
+use crate::api::{authorize, handle, Request, Response, Store};
+
+/// Largest request body accepted, which bounds an import upload.
+const MAX_BODY: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Parser)]
+#[command(name = "memory-pak-server", version, about)]
+struct Args {
+    /// Address to listen on. Use `0.0.0.0:7878` to accept LAN clients.
+    #[arg(long, default_value = "127.0.0.1:7878")]
+    listen: SocketAddr,
+    /// Bearer token every request must carry.
+    #[arg(long, env = "MEMORY_PAK_TOKEN", hide_env_values = true)]
+    token: String,
+    /// State file to serve instead of the desktop app's `state.json`.
+    #[arg(long, value_name = "PATH")]
+    state: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if args.token.trim().is_empty() {
+        eprintln!("memory-pak-server: the token must not be empty");
+        return ExitCode::FAILURE;
+    }
+    let path = match args
+        .state
+        .map_or_else(default_state_path, |path| Ok(Some(path)))
+    {
+        Ok(Some(path)) => path,
+        Ok(None) => {
+            eprintln!("memory-pak-server: no data directory on this platform; pass --state");
+            return ExitCode::FAILURE;
+        }
+        Err(err) => {
+            eprintln!("memory-pak-server: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut store = match Store::open(path.clone()) {
+        Ok(store) => store,
+        Err(err) => {
+            eprintln!("memory-pak-server: {}: {err}", path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let server = match Server::http(args.listen) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("memory-pak-server: cannot listen on {}: {err}", args.listen);
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!(
+        "memory-pak-server: serving {} on http://{}",
+        path.display(),
+        args.listen
+    );
+
+    // Requests are handled one at a time, so mutations never interleave.
+    for mut request in server.incoming_requests() {
+        let authorization = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Authorization"))
+            .map(|header| header.value.as_str().to_string());
+
+        let read =
+            authorize(&args.token, authorization.as_deref()).and_then(|()| read_body(&mut request));
+        let response = match read {
+            Ok(body) => handle(
+                &mut store,
+                &args.token,
+                &Request {
+                    method: request.method().as_str(),
+                    url: request.url(),
+                    authorization: authorization.as_deref(),
+                    body: &body,
+                },
+            ),
+            Err(response) => response,
+        };
+
+        let header = Header::from_bytes("Content-Type", response.content_type)
+            .expect("static header is valid");
+        let reply = tiny_http::Response::from_string(response.body)
+            .with_status_code(response.status)
+            .with_header(header);
+        if let Err(err) = request.respond(reply) {
+            eprintln!("memory-pak-server: failed to respond: {err}");
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// The whole body, or a 413 when it is over [`MAX_BODY`] rather than a
+/// truncated body that would be half imported.
+fn read_body(request: &mut tiny_http::Request) -> Result<String, Response> {
+    let too_large = || {
+        Response::error(
+            413,
+            format!("request body is over {} MiB", MAX_BODY / (1024 * 1024)),
+        )
+    };
+    if request
+        .body_length()
+        .is_some_and(|length| length as u64 > MAX_BODY)
+    {
+        return Err(too_large());
+    }
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY + 1)
+        .read_to_string(&mut body)
+        .map_err(|err| Response::error(400, err))?;
+    if body.len() as u64 > MAX_BODY {
+        return Err(too_large());
+    }
+    Ok(body)
+}
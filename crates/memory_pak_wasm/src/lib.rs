@@ -1,5 +1,11 @@
+use std::sync::{Arc, Mutex};
+
+use js_sys::Function;
 use memory_pak_core::{
-    MemoryPakApp, PersistedState, QueryInput, SetItemNotesInput, SetItemStatusInput,
+    parse_persisted_state, ActivityPolicy, ActivityQuery, BatchChange, ChangeEvent,
+    CsvImportOptions, EntryId, ImportOptions, MemoryPakApp, OrphanRepair, PersistedState,
+    QueryInput, QueryPatchInput, RenameListInput, SetItemCopiesInput, SetItemNotesInput,
+    SetItemPlayStatusInput, SetItemStatusInput, SetItemTagsInput, SetListInput, TimelineInput,
 };
 use serde::Serialize;
 use wasm_bindgen::prelude::*;
@@ -7,6 +13,11 @@ use wasm_bindgen::prelude::*;
 #[wasm_bindgen]
 pub struct WasmMemoryPak {
     app: MemoryPakApp,
+    /// Events from the core listener, waiting to be passed to `on_change`.
+    /// JS functions cannot be held by a `Send` listener, so they are
+    /// delivered once the mutating call returns.
+    changes: Arc<Mutex<Vec<ChangeEvent>>>,
+    on_change: Option<Function>,
 }
 
 #[wasm_bindgen]
@@ -15,16 +26,35 @@ impl WasmMemoryPak {
     pub fn new(state_json: Option<String>) -> Result<WasmMemoryPak, JsValue> {
         console_error_panic_hook::set_once();
         let state = match state_json {
-            Some(json) if !json.trim().is_empty() => serde_json::from_str::<PersistedState>(&json)
-                .map_err(|err| js_error(format!("Invalid persisted state: {err}")))?,
+            Some(json) if !json.trim().is_empty() => {
+                parse_persisted_state(&json).map_err(|err| js_error(err.to_string()))?
+            }
             _ => PersistedState::default(),
         };
 
+        let mut app = MemoryPakApp::from_persisted_state(state);
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&changes);
+        app.subscribe(move |event| {
+            if let Ok(mut pending) = sink.lock() {
+                pending.push(event.clone());
+            }
+        });
+
         Ok(Self {
-            app: MemoryPakApp::from_persisted_state(state),
+            app,
+            changes,
+            on_change: None,
         })
     }
 
+    /// Calls `callback` with a `ChangeEvent` after every call that changes
+    /// the state. Pass `undefined` to stop.
+    #[wasm_bindgen(js_name = onChange)]
+    pub fn on_change(&mut self, callback: Option<Function>) {
+        self.on_change = callback;
+    }
+
     #[wasm_bindgen(js_name = loadInitialState)]
     pub fn load_initial_state(&self) -> Result<JsValue, JsValue> {
         to_js(self.app.initial_state())
@@ -48,6 +78,15 @@ impl WasmMemoryPak {
         to_js(self.app.query_collectibles(input))
     }
 
+    #[wasm_bindgen(js_name = getItem)]
+    pub fn get_item(&self, id: String) -> Result<JsValue, JsValue> {
+        let item = self
+            .app
+            .get_item(&EntryId::from_raw(id))
+            .map_err(|err| js_error(err.to_string()))?;
+        to_js(item)
+    }
+
     #[wasm_bindgen(js_name = setItemStatus)]
     pub fn set_item_status(&mut self, input: JsValue) -> Result<JsValue, JsValue> {
         let input = from_js::<SetItemStatusInput>(input)?;
@@ -55,6 +94,7 @@ impl WasmMemoryPak {
             .app
             .set_item_status(input)
             .map_err(|err| js_error(err.to_string()))?;
+        self.dispatch_changes();
         to_js(result)
     }
 
@@ -65,16 +105,143 @@ impl WasmMemoryPak {
             .app
             .set_item_notes(input)
             .map_err(|err| js_error(err.to_string()))?;
+        self.dispatch_changes();
+        to_js(result)
+    }
+
+    #[wasm_bindgen(js_name = setItemCopies)]
+    pub fn set_item_copies(&mut self, input: JsValue) -> Result<JsValue, JsValue> {
+        let input = from_js::<SetItemCopiesInput>(input)?;
+        let result = self
+            .app
+            .set_item_copies(input)
+            .map_err(|err| js_error(err.to_string()))?;
+        self.dispatch_changes();
+        to_js(result)
+    }
+
+    #[wasm_bindgen(js_name = setItemPlayStatus)]
+    pub fn set_item_play_status(&mut self, input: JsValue) -> Result<JsValue, JsValue> {
+        let input = from_js::<SetItemPlayStatusInput>(input)?;
+        let result = self
+            .app
+            .set_item_play_status(input)
+            .map_err(|err| js_error(err.to_string()))?;
+        self.dispatch_changes();
+        to_js(result)
+    }
+
+    #[wasm_bindgen(js_name = setItemTags)]
+    pub fn set_item_tags(&mut self, input: JsValue) -> Result<JsValue, JsValue> {
+        let input = from_js::<SetItemTagsInput>(input)?;
+        let result = self
+            .app
+            .set_item_tags(input)
+            .map_err(|err| js_error(err.to_string()))?;
+        self.dispatch_changes();
+        to_js(result)
+    }
+
+    #[wasm_bindgen(js_name = setList)]
+    pub fn set_list(&mut self, input: JsValue) -> Result<JsValue, JsValue> {
+        let input = from_js::<SetListInput>(input)?;
+        let result = self
+            .app
+            .set_list(input)
+            .map_err(|err| js_error(err.to_string()))?;
+        self.dispatch_changes();
+        to_js(result)
+    }
+
+    #[wasm_bindgen(js_name = renameList)]
+    pub fn rename_list(&mut self, input: JsValue) -> Result<JsValue, JsValue> {
+        let input = from_js::<RenameListInput>(input)?;
+        let result = self
+            .app
+            .rename_list(input)
+            .map_err(|err| js_error(err.to_string()))?;
+        self.dispatch_changes();
+        to_js(result)
+    }
+
+    #[wasm_bindgen(js_name = deleteList)]
+    pub fn delete_list(&mut self, name: String) -> Result<(), JsValue> {
+        self.app
+            .delete_list(&name)
+            .map_err(|err| js_error(err.to_string()))?;
+        self.dispatch_changes();
+        Ok(())
+    }
+
+    /// Applies every change, or none if any id is unknown.
+    #[wasm_bindgen(js_name = applyBatch)]
+    pub fn apply_batch(&mut self, changes: JsValue) -> Result<JsValue, JsValue> {
+        let changes = from_js::<Vec<BatchChange>>(changes)?;
+        let result = self
+            .app
+            .apply_batch(changes)
+            .map_err(|err| js_error(err.to_string()))?;
+        self.dispatch_changes();
+        to_js(result)
+    }
+
+    /// Sets flags on every match of a query; `dryRun` only counts them.
+    #[wasm_bindgen(js_name = applyStatusToQuery)]
+    pub fn apply_status_to_query(&mut self, input: JsValue) -> Result<JsValue, JsValue> {
+        let input = from_js::<QueryPatchInput>(input)?;
+        let result = self
+            .app
+            .apply_status_to_query(input)
+            .map_err(|err| js_error(err.to_string()))?;
+        self.dispatch_changes();
         to_js(result)
     }
 
+    /// Reverts the last change; `undefined` when there is none.
+    pub fn undo(&mut self) -> Result<JsValue, JsValue> {
+        let result = self.app.undo();
+        self.dispatch_changes();
+        to_js(result)
+    }
+
+    /// Reapplies the last undone change; `undefined` when there is none.
+    pub fn redo(&mut self) -> Result<JsValue, JsValue> {
+        let result = self.app.redo();
+        self.dispatch_changes();
+        to_js(result)
+    }
+
+    #[wasm_bindgen(js_name = canUndo)]
+    pub fn can_undo(&self) -> bool {
+        self.app.can_undo()
+    }
+
+    #[wasm_bindgen(js_name = canRedo)]
+    pub fn can_redo(&self) -> bool {
+        self.app.can_redo()
+    }
+
+    /// `options` defaults to a merge with the overwrite strategy when omitted.
+    #[wasm_bindgen(js_name = previewImportJson)]
+    pub fn preview_import_json(&self, json: String, options: JsValue) -> Result<JsValue, JsValue> {
+        let options = from_js_or_default::<ImportOptions>(options)?;
+        let preview = self
+            .app
+            .preview_import_json(&json, options)
+            .map_err(|err| js_error(err.to_string()))?;
+        to_js(preview)
+    }
+
+    /// `options` defaults to a merge with the overwrite strategy when omitted.
     #[wasm_bindgen(js_name = importJson)]
-    pub fn import_json(&mut self, json: String) -> Result<JsValue, JsValue> {
-        let stats = self
+    pub fn import_json(&mut self, json: String, options: JsValue) -> Result<JsValue, JsValue> {
+        let options = from_js_or_default::<ImportOptions>(options)?;
+        let summary = self
             .app
-            .import_json(&json)
+            .import_json(&json, options)
             .map_err(|err| js_error(err.to_string()))?;
-        to_js(stats)
+        self.dispatch_changes();
+        to_js(summary)
     }
 
     #[wasm_bindgen(js_name = exportJson)]
@@ -84,11 +251,101 @@ impl WasmMemoryPak {
             .map_err(|err| js_error(format!("Failed to export JSON: {err}")))
     }
 
+    /// `options` may be omitted to use the export's own column names.
+    #[wasm_bindgen(js_name = importCsv)]
+    pub fn import_csv(&mut self, csv: String, options: JsValue) -> Result<JsValue, JsValue> {
+        let options = from_js_or_default::<CsvImportOptions>(options)?;
+        let report = self
+            .app
+            .import_csv(&csv, options)
+            .map_err(|err| js_error(err.to_string()))?;
+        self.dispatch_changes();
+        to_js(report)
+    }
+
+    #[wasm_bindgen(js_name = exportCsv)]
+    pub fn export_csv(&self) -> String {
+        self.app.export_csv()
+    }
+
     #[wasm_bindgen(js_name = getCollectionStats)]
     pub fn get_collection_stats(&self) -> Result<JsValue, JsValue> {
         to_js(self.app.collection_stats())
     }
 
+    #[wasm_bindgen(js_name = listOrphans)]
+    pub fn list_orphans(&self) -> Result<JsValue, JsValue> {
+        to_js(self.app.orphans())
+    }
+
+    /// Applies all repairs or, if any is invalid, none of them.
+    #[wasm_bindgen(js_name = repairOrphans)]
+    pub fn repair_orphans(&mut self, repairs: JsValue) -> Result<JsValue, JsValue> {
+        let repairs = from_js::<Vec<OrphanRepair>>(repairs)?;
+        let summary = self
+            .app
+            .repair_orphans(repairs)
+            .map_err(|err| js_error(err.to_string()))?;
+        self.dispatch_changes();
+        to_js(summary)
+    }
+
+    #[wasm_bindgen(js_name = getSpendingReport)]
+    pub fn get_spending_report(&self) -> Result<JsValue, JsValue> {
+        to_js(self.app.spending_report())
+    }
+
+    /// `input` defaults to monthly buckets up to today when omitted.
+    #[wasm_bindgen(js_name = growthTimeline)]
+    pub fn growth_timeline(&self, input: JsValue) -> Result<JsValue, JsValue> {
+        let input = from_js_or_default::<TimelineInput>(input)?;
+        let report = self
+            .app
+            .growth_timeline(input)
+            .map_err(|err| js_error(err.to_string()))?;
+        to_js(report)
+    }
+
+    /// `query` defaults to the newest events when omitted.
+    #[wasm_bindgen(js_name = recentActivity)]
+    pub fn recent_activity(&self, query: JsValue) -> Result<JsValue, JsValue> {
+        let query = from_js_or_default::<ActivityQuery>(query)?;
+        to_js(self.app.recent_activity(query))
+    }
+
+    #[wasm_bindgen(js_name = entryHistory)]
+    pub fn entry_history(&self, id: String) -> Result<JsValue, JsValue> {
+        to_js(self.app.entry_history(&EntryId::from_raw(id)))
+    }
+
+    #[wasm_bindgen(js_name = activityPolicy)]
+    pub fn activity_policy(&self) -> Result<JsValue, JsValue> {
+        to_js(self.app.activity_policy())
+    }
+
+    /// `null` keeps all activity. Returns how many events were dropped.
+    #[wasm_bindgen(js_name = setActivityPolicy)]
+    pub fn set_activity_policy(&mut self, policy: JsValue) -> Result<usize, JsValue> {
+        let policy = if policy.is_undefined() || policy.is_null() {
+            None
+        } else {
+            Some(from_js::<ActivityPolicy>(policy)?)
+        };
+        let dropped = self.app.set_activity_policy(policy);
+        self.dispatch_changes();
+        Ok(dropped)
+    }
+
+    /// Replaces the state with one saved by another tab. Clears undo
+    /// history and reports what differs through `onChange`.
+    #[wasm_bindgen(js_name = reloadState)]
+    pub fn reload_state(&mut self, state_json: String) -> Result<(), JsValue> {
+        let state = parse_persisted_state(&state_json).map_err(|err| js_error(err.to_string()))?;
+        self.app.reload_state(state);
+        self.dispatch_changes();
+        Ok(())
+    }
+
     #[wasm_bindgen(js_name = snapshotStateJson)]
     pub fn snapshot_state_json(&self) -> Result<String, JsValue> {
         serde_json::to_string(self.app.persisted_state())
@@ -96,6 +353,25 @@ impl WasmMemoryPak {
     }
 }
 
+impl WasmMemoryPak {
+    fn dispatch_changes(&self) {
+        let pending = match self.changes.lock() {
+            Ok(mut pending) => std::mem::take(&mut *pending),
+            Err(_) => return,
+        };
+        let Some(callback) = &self.on_change else {
+            return;
+        };
+        for event in pending {
+            // The change is already applied; a failing callback must not
+            // turn it into an error for the caller.
+            if let Ok(event) = to_js(event) {
+                let _ = callback.call1(&JsValue::NULL, &event);
+            }
+        }
+    }
+}
+
 fn from_js<T>(value: JsValue) -> Result<T, JsValue>
 where
     T: serde::de::DeserializeOwned,
@@ -103,11 +379,27 @@ where
     serde_wasm_bindgen::from_value(value).map_err(|err| js_error(err.to_string()))
 }
 
+/// Like `from_js`, but `undefined` and `null` give `T::default()`.
+fn from_js_or_default<T>(value: JsValue) -> Result<T, JsValue>
+where
+    T: serde::de::DeserializeOwned + Default,
+{
+    if value.is_undefined() || value.is_null() {
+        Ok(T::default())
+    } else {
+        from_js(value)
+    }
+}
+
 fn to_js<T>(value: T) -> Result<JsValue, JsValue>
 where
     T: Serialize,
 {
-    serde_wasm_bindgen::to_value(&value).map_err(|err| js_error(err.to_string()))
+    // Flattened fields serialize as a map, which should still be an object.
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    value
+        .serialize(&serializer)
+        .map_err(|err| js_error(err.to_string()))
 }
 
 fn js_error(message: impl Into<String>) -> JsValue {
@@ -58,7 +58,8 @@ export function rowMatchesFilter(state: EntryState, filter: FilterBy): boolean {
   if (filter === 'owned') return state.owned;
   if (filter === 'favorites') return state.favorite;
   if (filter === 'wishlist') return state.wishlist;
-  return !state.owned;
+  if (filter === 'notOwned') return !state.owned;
+  return state.playStatus === filter;
 }
 
 export function estimatedRowHeight(tab: TabId, mobile: boolean, short: boolean): number {
@@ -14,20 +14,23 @@ export function getSortOptions(tab: TabId): SortOption[] {
       { id: 'group', label: 'Pack / Game' },
       { id: 'variant', label: 'Variant' },
       { id: 'year', label: 'Year' },
-      { id: 'status', label: 'Status' }
+      { id: 'status', label: 'Status' },
+      { id: 'relevance', label: 'Relevance' }
     ];
   }
   if (tab === 'consoles') {
     return [
       { id: 'name', label: 'Name' },
       { id: 'manufacturer', label: 'Manufacturer' },
-      { id: 'status', label: 'Status' }
+      { id: 'status', label: 'Status' },
+      { id: 'relevance', label: 'Relevance' }
     ];
   }
   return [
     { id: 'title', label: 'Title' },
     { id: 'year', label: 'Year' },
-    { id: 'status', label: 'Status' }
+    { id: 'status', label: 'Status' },
+    { id: 'relevance', label: 'Relevance' }
   ];
 }
 
@@ -1,17 +1,48 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { open, save } from '@tauri-apps/plugin-dialog';
 import type {
+  ActivityEvent,
+  ActivityPolicy,
+  ActivityQuery,
+  ActivityView,
+  BatchChange,
+  BatchResult,
+  ChangeEvent,
   CollectibleView,
   CollectionStats,
   ConsoleView,
+  CsvImportOptions,
+  EntryId,
+  EntryList,
+  FileImportResult,
   GameView,
+  HistoryResult,
+  ImportPreview,
+  ImportOptions,
+  ImportReport,
+  ImportSummary,
   InitialState,
   MemoryPakBackend,
   MutationResult,
+  OrphanEntry,
+  OrphanRepair,
+  OrphanRepairSummary,
   QueryInput,
+  QueryPatchInput,
+  QueryPatchResult,
   QueryResult,
+  RenameListInput,
+  RowView,
+  SetItemCopiesInput,
   SetItemNotesInput,
-  SetItemStatusInput
+  SetItemPlayStatusInput,
+  SetItemStatusInput,
+  SetItemTagsInput,
+  SetListInput,
+  SpendingReport,
+  TimelineInput,
+  TimelineReport
 } from './types';
 
 export function createTauriBackend(): MemoryPakBackend {
@@ -22,24 +53,63 @@ export function createTauriBackend(): MemoryPakBackend {
     queryGames: (input: QueryInput) => invoke<QueryResult<GameView>>('query_games', { input }),
     queryCollectibles: (input: QueryInput) =>
       invoke<QueryResult<CollectibleView>>('query_collectibles', { input }),
+    getItem: (id: EntryId) => invoke<RowView>('get_item', { id }),
     setItemStatus: (input: SetItemStatusInput) =>
       invoke<MutationResult>('set_item_status', { input }),
     setItemNotes: (input: SetItemNotesInput) => invoke<MutationResult>('set_item_notes', { input }),
-    importJson: (json: string) => invoke<CollectionStats>('import_json', { json }),
+    setItemCopies: (input: SetItemCopiesInput) =>
+      invoke<MutationResult>('set_item_copies', { input }),
+    setItemPlayStatus: (input: SetItemPlayStatusInput) =>
+      invoke<MutationResult>('set_item_play_status', { input }),
+    setItemTags: (input: SetItemTagsInput) => invoke<MutationResult>('set_item_tags', { input }),
+    setList: (input: SetListInput) => invoke<EntryList>('set_list', { input }),
+    renameList: (input: RenameListInput) => invoke<EntryList>('rename_list', { input }),
+    deleteList: (name: string) => invoke<void>('delete_list', { name }),
+    applyBatch: (changes: BatchChange[]) => invoke<BatchResult>('apply_batch', { changes }),
+    applyStatusToQuery: (input: QueryPatchInput) =>
+      invoke<QueryPatchResult>('apply_status_to_query', { input }),
+    undo: () => invoke<HistoryResult | null>('undo'),
+    redo: () => invoke<HistoryResult | null>('redo'),
+    canUndo: () => invoke<boolean>('can_undo'),
+    canRedo: () => invoke<boolean>('can_redo'),
+    previewImportJson: (json: string, options?: ImportOptions) =>
+      invoke<ImportPreview>('preview_import_json', { json, options }),
+    importJson: (json: string, options?: ImportOptions) =>
+      invoke<ImportSummary>('import_json', { json, options }),
     exportJson: () => invoke<string>('export_json'),
+    importCsv: (csv: string, options?: CsvImportOptions) =>
+      invoke<ImportReport>('import_csv', { csv, options }),
+    exportCsv: () => invoke<string>('export_csv'),
     getCollectionStats: () => invoke<CollectionStats>('get_collection_stats'),
-    importFromFile: async () => {
+    listOrphans: () => invoke<OrphanEntry[]>('list_orphans'),
+    repairOrphans: (repairs: OrphanRepair[]) =>
+      invoke<OrphanRepairSummary>('repair_orphans', { repairs }),
+    getSpendingReport: () => invoke<SpendingReport>('get_spending_report'),
+    growthTimeline: (input?: TimelineInput) => invoke<TimelineReport>('growth_timeline', { input }),
+    recentActivity: (query?: ActivityQuery) => invoke<ActivityView[]>('recent_activity', { query }),
+    entryHistory: (id: EntryId) => invoke<ActivityEvent[]>('entry_history', { id }),
+    activityPolicy: () => invoke<ActivityPolicy | null>('activity_policy'),
+    setActivityPolicy: (policy: ActivityPolicy | null) =>
+      invoke<number>('set_activity_policy', { policy }),
+    onStateChange: (listener: (event: ChangeEvent) => void) => {
+      const unlisten = listen<ChangeEvent>('state-changed', (event) => listener(event.payload));
+      return () => void unlisten.then((stop) => stop());
+    },
+    importFromFile: async (options?: ImportOptions, csvOptions?: CsvImportOptions) => {
       const path = await open({
         multiple: false,
-        filters: [{ name: 'Memory Pak Export', extensions: ['json'] }]
+        filters: [{ name: 'Memory Pak Export', extensions: ['json', 'csv'] }]
       });
       if (typeof path !== 'string') return undefined;
-      return invoke<CollectionStats>('import_from_path', { path });
+      return invoke<FileImportResult>('import_from_path', { path, options, csvOptions });
     },
     exportToFile: async () => {
       const path = await save({
         defaultPath: 'memory_pak_export.json',
-        filters: [{ name: 'Memory Pak Export', extensions: ['json'] }]
+        filters: [
+          { name: 'Memory Pak Export', extensions: ['json'] },
+          { name: 'Spreadsheet (CSV)', extensions: ['csv'] }
+        ]
       });
       if (!path) return;
       await invoke('export_to_path', { path });
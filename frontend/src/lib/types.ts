@@ -1,6 +1,17 @@
 export type ItemKind = 'console' | 'game' | 'collectible';
 export type TabId = 'consoles' | 'games' | 'collectibles';
-export type FilterBy = 'all' | 'owned' | 'favorites' | 'wishlist' | 'notOwned';
+export type FilterBy =
+  | 'all'
+  | 'owned'
+  | 'favorites'
+  | 'wishlist'
+  | 'notOwned'
+  | 'backlog'
+  | 'playing'
+  | 'beaten'
+  | 'completed'
+  | 'abandoned';
+export type PlayStatus = 'playing' | 'backlog' | 'beaten' | 'completed' | 'abandoned';
 export type SortKey =
   | 'title'
   | 'name'
@@ -10,22 +21,51 @@ export type SortKey =
   | 'group'
   | 'collection'
   | 'variant'
-  | 'manufacturer';
+  | 'manufacturer'
+  | 'releaseDate'
+  | 'playStatus'
+  | 'relevance';
+export type Region = 'jp' | 'na' | 'pal' | 'eu' | 'au' | 'br';
 
 /** Wire form of an EntryId; structurally `kind:locator`. */
 export type EntryId = string;
 
+export type Completeness = 'loose' | 'cib' | 'sealed';
+export type Condition = 'mint' | 'nearMint' | 'veryGood' | 'good' | 'fair' | 'poor';
+
+export interface OwnedCopy {
+  condition?: Condition | null;
+  completeness?: Completeness | null;
+  region?: Region | null;
+  notes?: string;
+  /** Minor units of `currency` (cents, pence, yen). */
+  purchasePrice?: number | null;
+  /** ISO 4217 code such as `USD`. */
+  currency?: string | null;
+  /** `YYYY-MM-DD`. */
+  acquiredOn?: string | null;
+  source?: string | null;
+}
+
 export interface EntryState {
+  /** Mirrors `copies.length > 0`. */
   owned: boolean;
   favorite: boolean;
   wishlist: boolean;
   notes: string;
+  copies?: OwnedCopy[];
+  tags?: string[];
+  playStatus?: PlayStatus | null;
+  /** `YYYY-MM-DD`. */
+  completedOn?: string | null;
 }
 
 export interface ConsoleCounts {
   owned: number;
+  ownedCopies: number;
   favorite: number;
   wishlist: number;
+  backlog: number;
 }
 
 export interface ConsoleView {
@@ -40,6 +80,16 @@ export interface ConsoleView {
   gameCounts: ConsoleCounts;
 }
 
+/** ISO `YYYY`, `YYYY-MM` or `YYYY-MM-DD` release dates per region. */
+export interface Releases {
+  jp: string | null;
+  na: string | null;
+  pal: string | null;
+  eu: string | null;
+  au: string | null;
+  br: string | null;
+}
+
 export interface GameView {
   kind: 'game';
   id: EntryId;
@@ -49,6 +99,7 @@ export interface GameView {
   publisher: string;
   consoleId: EntryId;
   consoleName: string;
+  releases: Releases;
   state: EntryState;
 }
 
@@ -79,18 +130,139 @@ export type RowView = ConsoleView | GameView | CollectibleView;
 export interface CollectionStats {
   totalConsoles: number;
   ownedConsoles: number;
+  ownedConsoleCopies: number;
   favoriteConsoles: number;
   wishlistConsoles: number;
   totalGames: number;
   ownedGames: number;
+  ownedGameCopies: number;
   favoriteGames: number;
   wishlistGames: number;
   totalCollectibles: number;
   ownedCollectibles: number;
+  ownedCollectibleCopies: number;
   favoriteCollectibles: number;
   wishlistCollectibles: number;
 }
 
+/** Header names to read each field from; defaults match `exportCsv`. */
+export interface CsvColumns {
+  id?: string;
+  title?: string;
+  console?: string;
+  owned?: string;
+  favorite?: string;
+  wishlist?: string;
+  notes?: string;
+  playStatus?: string;
+  completedOn?: string;
+  tags?: string;
+}
+
+export interface CsvImportOptions {
+  columns?: CsvColumns;
+  matchTitleConsole?: boolean;
+}
+
+export interface UnmatchedRow {
+  line: number;
+  key: string;
+  reason: string;
+}
+
+export type ImportStrategy = 'overwrite' | 'keepLocal' | 'mergeFlags' | 'concatNotes';
+
+/** `replace` drops entries and lists the file does not mention. */
+export type ImportMode = 'merge' | 'replace';
+
+export interface ImportOptions {
+  mode?: ImportMode;
+  /** Ignored in replace mode. */
+  strategy?: ImportStrategy;
+}
+
+export interface ImportSummary {
+  stats: CollectionStats;
+  added: number;
+  updated: number;
+  removed: number;
+  renamed: IdRewrite[];
+  unresolved: EntryId[];
+}
+
+export interface FieldChange {
+  field: string;
+  old: unknown;
+  new: unknown;
+}
+
+export interface EntryChange {
+  id: EntryId;
+  fields: FieldChange[];
+}
+
+export interface ImportPreview {
+  added: EntryId[];
+  changed: EntryChange[];
+  cleared: EntryId[];
+  /** Ids missing from the catalog; stored as-is on apply. */
+  unknown: EntryId[];
+  renamed: IdRewrite[];
+  unchanged: number;
+  /** Lists the file would create. */
+  addedLists: string[];
+  /** Local lists whose members would change. */
+  changedLists: string[];
+  /** Local lists a replace-mode file drops. */
+  removedLists: string[];
+}
+
+export interface ImportReport {
+  stats: CollectionStats;
+  /** Only counted for CSV imports. */
+  matched: number;
+  unmatched?: UnmatchedRow[];
+}
+
+/** What importing a file did, tagged by the file's format. */
+export type FileImportResult =
+  | ({ format: 'csv' } & ImportReport)
+  | ({ format: 'json' } & ImportSummary);
+
+export interface SpendTotal {
+  currency: string;
+  amount: number;
+  copies: number;
+}
+
+export interface SpendGroup {
+  id: string;
+  name: string;
+  totals: SpendTotal[];
+}
+
+export interface YearSpend {
+  year: number;
+  totals: SpendTotal[];
+}
+
+export interface SpendingReport {
+  totals: SpendTotal[];
+  byConsole: SpendGroup[];
+  byCollection: SpendGroup[];
+  byYear: YearSpend[];
+}
+
+export interface EntryList {
+  name: string;
+  entries: EntryId[];
+}
+
+export interface TagCount {
+  tag: string;
+  count: number;
+}
+
 export interface InitialState {
   stats: CollectionStats;
   consoles: ConsoleView[];
@@ -99,21 +271,85 @@ export interface InitialState {
   collections: CollectionView[];
   totalGames: number;
   totalCollectibles: number;
+  tags: TagCount[];
+  lists: EntryList[];
+  /** Saved ids that were renamed in the catalog, or that it no longer knows. */
+  idReport: IdReport;
+}
+
+export interface IdRewrite {
+  from: EntryId;
+  to: EntryId;
+}
+
+export interface IdReport {
+  rewritten: IdRewrite[];
+  /** Kept as-is; nothing in the catalog matches them. */
+  unresolved: EntryId[];
+}
+
+export interface OrphanSuggestion {
+  id: EntryId;
+  title: string;
+  /** Slug similarity, 0-100. */
+  score: number;
+}
+
+/** A saved id the catalog no longer has. */
+export interface OrphanEntry {
+  id: EntryId;
+  state: EntryState;
+  lists: string[];
+  suggestions: OrphanSuggestion[];
+}
+
+export type OrphanRepair =
+  | { action: 'remap'; id: EntryId; to: EntryId }
+  | { action: 'discard'; id: EntryId };
+
+export interface OrphanRepairSummary {
+  stats: CollectionStats;
+  remapped: number;
+  discarded: number;
+}
+
+/** Sent after every call that changes the state, including from other windows. */
+export interface ChangeEvent {
+  /** Entries whose saved state changed. */
+  ids: EntryId[];
+  /** Lists created, changed or deleted; a renamed list appears under both names. */
+  lists: string[];
+  stats: CollectionStats;
 }
 
 export interface QueryInput {
+  /** Plain text or field syntax such as `publisher:capcom year:1990..1995 -owned`. */
   search?: string;
   sortBy?: SortKey;
   filterBy?: FilterBy;
   consoleId?: EntryId | 'all';
   collectionId?: string | 'all';
+  /** Games only; `pal` also matches EU/AU release columns. */
+  region?: Region;
+  /** Entries carrying every listed tag. */
+  tags?: string[];
+  /** Name of a custom list. */
+  list?: string;
   offset?: number;
   limit?: number;
 }
 
+/** Search parse failure; `start`/`end` are character offsets into `search`. */
+export interface QuerySyntaxError {
+  start: number;
+  end: number;
+  message: string;
+}
+
 export interface QueryResult<T> {
   total: number;
   items: T[];
+  error?: QuerySyntaxError;
 }
 
 export interface SetItemStatusInput {
@@ -128,14 +364,175 @@ export interface SetItemNotesInput {
   notes: string;
 }
 
+export interface SetItemPlayStatusInput {
+  id: EntryId;
+  playStatus: PlayStatus | null;
+  completedOn?: string | null;
+}
+
+export interface SetItemTagsInput {
+  id: EntryId;
+  tags: string[];
+}
+
+export interface SetListInput {
+  name: string;
+  entries: EntryId[];
+}
+
+export interface RenameListInput {
+  name: string;
+  newName: string;
+}
+
+export interface SetItemCopiesInput {
+  id: EntryId;
+  copies: OwnedCopy[];
+}
+
 export interface MutationResult {
   id: EntryId;
   state: EntryState;
   stats: CollectionStats;
 }
 
+/** Flags to set; omitted flags are left as they are. */
+export interface StatusPatch {
+  owned?: boolean;
+  favorite?: boolean;
+  wishlist?: boolean;
+}
+
+export interface QueryPatchInput {
+  kind: ItemKind;
+  /** `offset` and `limit` are ignored: the patch covers every match. */
+  query?: QueryInput;
+  patch: StatusPatch;
+  dryRun?: boolean;
+}
+
+export interface QueryPatchResult {
+  matched: number;
+  /** Matches whose state changed, or would change in a dry run. */
+  affected: number;
+  dryRun: boolean;
+  stats: CollectionStats;
+}
+
+export type BatchChange =
+  | ({ op: 'status' } & SetItemStatusInput)
+  | ({ op: 'notes' } & SetItemNotesInput);
+
+export interface BatchItemResult {
+  id: EntryId;
+  state: EntryState;
+}
+
+export interface HistoryResult {
+  /** Restored entries; one that no longer exists has the default state. */
+  items: BatchItemResult[];
+  lists: string[];
+  stats: CollectionStats;
+  canUndo: boolean;
+  canRedo: boolean;
+}
+
+export interface BatchResult {
+  /** One per change, in order, with the state right after that change. */
+  items: BatchItemResult[];
+  stats: CollectionStats;
+}
+
+export type TimeBucket = 'week' | 'month' | 'year';
+
+/** The range is widened to whole buckets; over 5,218 buckets is rejected. */
+export interface TimelineInput {
+  /** Defaults to `month`. */
+  bucket?: TimeBucket;
+  /**
+   * `YYYY-MM-DD`; defaults to the earliest acquisition, but not before the
+   * catalog's first release year.
+   */
+  from?: string;
+  /** `YYYY-MM-DD`; defaults to today. */
+  to?: string;
+}
+
+export interface TimelinePoint {
+  /** Copies acquired during the bucket. */
+  acquired: number;
+  /** Entries owned by the end of the bucket. */
+  owned: number;
+  /** Copies owned by the end of the bucket. */
+  ownedCopies: number;
+  /** What the copies acquired during the bucket cost. */
+  spent: SpendTotal[];
+}
+
+export interface TimelineSeries {
+  /** Console `EntryId` or collection id. */
+  id: string;
+  name: string;
+  points: TimelinePoint[];
+}
+
+/** Every `points` array has one point per entry of `buckets`. */
+export interface TimelineReport {
+  bucket: TimeBucket;
+  /** First day of each bucket as `YYYY-MM-DD`, oldest first. */
+  buckets: string[];
+  total: TimelinePoint[];
+  byConsole: TimelineSeries[];
+  byCollection: TimelineSeries[];
+  /** Owned copies with neither an acquisition date nor recorded activity. */
+  undatedCopies: number;
+}
+
+/** One field of an entry going from `old` to `new`. */
+export type ActivityChange =
+  | { field: 'owned' | 'favorite' | 'wishlist'; old: boolean; new: boolean }
+  | { field: 'notes'; old: string; new: string }
+  | { field: 'copies'; old: OwnedCopy[]; new: OwnedCopy[] }
+  | { field: 'playStatus'; old?: PlayStatus | null; new?: PlayStatus | null }
+  | { field: 'completedOn'; old?: string | null; new?: string | null }
+  | { field: 'tags'; old: string[]; new: string[] };
+
+export type ActivityEvent = ActivityChange & {
+  /** RFC 3339 UTC timestamp. */
+  at: string;
+  id: EntryId;
+};
+
+export type ActivityView = ActivityEvent & {
+  /** Missing when the catalog no longer has the entry. */
+  name?: string | null;
+};
+
+export interface ActivityQuery {
+  /** Defaults to 50. */
+  limit?: number;
+  /** RFC 3339; only events at or after it. */
+  since?: string;
+}
+
+/** Limits applied after every change. Both unset keeps everything. */
+export interface ActivityPolicy {
+  maxEvents?: number | null;
+  maxAgeDays?: number | null;
+}
+
+export interface ActivityLog {
+  /** Oldest first. */
+  events?: ActivityEvent[];
+  policy?: ActivityPolicy | null;
+}
+
 export interface PersistedState {
+  /** Schema version; missing on states written before versioning. The core upgrades older versions on load. */
+  version?: number;
   entries: Record<EntryId, EntryState>;
+  lists?: EntryList[];
+  activity?: ActivityLog;
 }
 
 export interface MemoryPakBackend {
@@ -143,12 +540,49 @@ export interface MemoryPakBackend {
   queryConsoles(input: QueryInput): Promise<QueryResult<ConsoleView>>;
   queryGames(input: QueryInput): Promise<QueryResult<GameView>>;
   queryCollectibles(input: QueryInput): Promise<QueryResult<CollectibleView>>;
+  getItem(id: EntryId): Promise<RowView>;
   setItemStatus(input: SetItemStatusInput): Promise<MutationResult>;
   setItemNotes(input: SetItemNotesInput): Promise<MutationResult>;
-  importJson(json: string): Promise<CollectionStats>;
+  setItemCopies(input: SetItemCopiesInput): Promise<MutationResult>;
+  setItemPlayStatus(input: SetItemPlayStatusInput): Promise<MutationResult>;
+  setItemTags(input: SetItemTagsInput): Promise<MutationResult>;
+  setList(input: SetListInput): Promise<EntryList>;
+  renameList(input: RenameListInput): Promise<EntryList>;
+  deleteList(name: string): Promise<void>;
+  /** All-or-nothing: one unknown id rejects the whole batch. */
+  applyBatch(changes: BatchChange[]): Promise<BatchResult>;
+  applyStatusToQuery(input: QueryPatchInput): Promise<QueryPatchResult>;
+  /** `null` when there is nothing to undo. */
+  undo(): Promise<HistoryResult | null>;
+  /** `null` when there is nothing to redo. */
+  redo(): Promise<HistoryResult | null>;
+  canUndo(): Promise<boolean>;
+  canRedo(): Promise<boolean>;
+  previewImportJson(json: string, options?: ImportOptions): Promise<ImportPreview>;
+  importJson(json: string, options?: ImportOptions): Promise<ImportSummary>;
   exportJson(): Promise<string>;
+  importCsv(csv: string, options?: CsvImportOptions): Promise<ImportReport>;
+  exportCsv(): Promise<string>;
   getCollectionStats(): Promise<CollectionStats>;
-  importFromFile?(): Promise<CollectionStats | undefined>;
+  listOrphans(): Promise<OrphanEntry[]>;
+  /** All-or-nothing: one invalid repair rejects the whole batch. */
+  repairOrphans(repairs: OrphanRepair[]): Promise<OrphanRepairSummary>;
+  getSpendingReport(): Promise<SpendingReport>;
+  growthTimeline(input?: TimelineInput): Promise<TimelineReport>;
+  /** Newest first. */
+  recentActivity(query?: ActivityQuery): Promise<ActivityView[]>;
+  /** Oldest first. */
+  entryHistory(id: EntryId): Promise<ActivityEvent[]>;
+  activityPolicy(): Promise<ActivityPolicy | null>;
+  /** `null` keeps all activity. Resolves to how many events were dropped. */
+  setActivityPolicy(policy: ActivityPolicy | null): Promise<number>;
+  /** Returns a function that stops the subscription. */
+  onStateChange(listener: (event: ChangeEvent) => void): () => void;
+  /** `options` apply to JSON exports and `csvOptions` to `.csv` files. */
+  importFromFile?(
+    options?: ImportOptions,
+    csvOptions?: CsvImportOptions
+  ): Promise<FileImportResult | undefined>;
   exportToFile?(): Promise<void>;
 }
 
@@ -38,4 +38,38 @@ describe('web storage (IndexedDB)', () => {
     expect(state.entries['console:nes']?.owned).toBe(true);
     expect(state.entries['game:nes/super-mario-bros']?.notes).toBe('cart only');
   });
+
+  it('round-trips custom lists through IndexedDB', async () => {
+    const { loadPersistedState, savePersistedState } = await import('./webStorage');
+    await savePersistedState({
+      entries: {},
+      lists: [{ name: 'to sell', entries: ['console:nes', 'game:nes/super-mario-bros'] }]
+    });
+
+    const state = await loadPersistedState();
+    expect(state.lists).toEqual([
+      { name: 'to sell', entries: ['console:nes', 'game:nes/super-mario-bros'] }
+    ]);
+  });
+
+  it('round-trips the activity log and its policy through IndexedDB', async () => {
+    const { loadPersistedState, savePersistedState } = await import('./webStorage');
+    const activity = {
+      events: [
+        {
+          at: '2024-05-01T18:30:00Z',
+          id: 'console:nes',
+          field: 'wishlist' as const,
+          old: false,
+          new: true
+        }
+      ],
+      policy: { maxEvents: 500, maxAgeDays: null }
+    };
+    await savePersistedState({ version: 1, entries: {}, activity });
+
+    const state = await loadPersistedState();
+    expect(state.version).toBe(1);
+    expect(state.activity).toEqual(activity);
+  });
 });
@@ -41,8 +41,14 @@ export async function loadPersistedState(): Promise<PersistedState> {
   try {
     const value = await withStore('readonly', (store) => store.get(KEY) as IDBRequest<unknown>);
     if (!value || typeof value !== 'object') return { entries: {} };
+    // Keep every stored field, such as `activity`; only default the
+    // required ones.
     const candidate = value as Partial<PersistedState>;
-    return { entries: candidate.entries ?? {} };
+    return {
+      ...candidate,
+      entries: candidate.entries ?? {},
+      lists: candidate.lists ?? [],
+    };
   } catch (error) {
     console.warn('Memory Pak: failed to read persisted state', error);
     return { entries: {} };
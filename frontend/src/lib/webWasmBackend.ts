@@ -1,32 +1,90 @@
 import type {
+  ActivityEvent,
+  ActivityPolicy,
+  ActivityQuery,
+  ActivityView,
+  BatchChange,
+  BatchResult,
+  ChangeEvent,
   CollectibleView,
   CollectionStats,
   ConsoleView,
+  CsvImportOptions,
+  EntryId,
+  EntryList,
+  FileImportResult,
   GameView,
+  HistoryResult,
+  ImportPreview,
+  ImportOptions,
+  ImportReport,
+  ImportSummary,
   InitialState,
   MemoryPakBackend,
   MutationResult,
+  OrphanEntry,
+  OrphanRepair,
+  OrphanRepairSummary,
   PersistedState,
   QueryInput,
+  QueryPatchInput,
+  QueryPatchResult,
   QueryResult,
+  RenameListInput,
+  RowView,
+  SetItemCopiesInput,
   SetItemNotesInput,
-  SetItemStatusInput
+  SetItemPlayStatusInput,
+  SetItemStatusInput,
+  SetItemTagsInput,
+  SetListInput,
+  SpendingReport,
+  TimelineInput,
+  TimelineReport
 } from './types';
 import { loadPersistedState, savePersistedState } from './webStorage';
 
 const SAVE_DEBOUNCE_MS = 250;
+/** Tabs announce their saves here so the others can reload. */
+const SYNC_CHANNEL = 'memory-pak-state';
 
 interface WasmInstance {
   loadInitialState(): InitialState;
   queryConsoles(input: QueryInput): QueryResult<ConsoleView>;
   queryGames(input: QueryInput): QueryResult<GameView>;
   queryCollectibles(input: QueryInput): QueryResult<CollectibleView>;
+  getItem(id: EntryId): RowView;
   setItemStatus(input: SetItemStatusInput): MutationResult;
   setItemNotes(input: SetItemNotesInput): MutationResult;
-  importJson(json: string): CollectionStats;
+  setItemCopies(input: SetItemCopiesInput): MutationResult;
+  setItemPlayStatus(input: SetItemPlayStatusInput): MutationResult;
+  setItemTags(input: SetItemTagsInput): MutationResult;
+  setList(input: SetListInput): EntryList;
+  renameList(input: RenameListInput): EntryList;
+  deleteList(name: string): void;
+  applyBatch(changes: BatchChange[]): BatchResult;
+  applyStatusToQuery(input: QueryPatchInput): QueryPatchResult;
+  undo(): HistoryResult | undefined;
+  redo(): HistoryResult | undefined;
+  canUndo(): boolean;
+  canRedo(): boolean;
+  previewImportJson(json: string, options?: ImportOptions): ImportPreview;
+  importJson(json: string, options?: ImportOptions): ImportSummary;
   exportJson(): string;
+  importCsv(csv: string, options?: CsvImportOptions): ImportReport;
+  exportCsv(): string;
   getCollectionStats(): CollectionStats;
+  listOrphans(): OrphanEntry[];
+  repairOrphans(repairs: OrphanRepair[]): OrphanRepairSummary;
+  getSpendingReport(): SpendingReport;
+  growthTimeline(input?: TimelineInput): TimelineReport;
+  recentActivity(query?: ActivityQuery): ActivityView[];
+  entryHistory(id: EntryId): ActivityEvent[];
+  activityPolicy(): ActivityPolicy | undefined;
+  setActivityPolicy(policy: ActivityPolicy | null): number;
+  reloadState(stateJson: string): void;
   snapshotStateJson(): string;
+  onChange(callback?: (event: ChangeEvent) => void): void;
 }
 
 export async function createWebWasmBackend(): Promise<MemoryPakBackend> {
@@ -35,13 +93,34 @@ export async function createWebWasmBackend(): Promise<MemoryPakBackend> {
   const persisted = await loadPersistedState();
   const app = new module.WasmMemoryPak(JSON.stringify(persisted)) as WasmInstance;
 
-  const queueSave = makeDebouncedSave(app);
+  const channel =
+    typeof BroadcastChannel === 'undefined' ? null : new BroadcastChannel(SYNC_CHANNEL);
+  const saver = makeDebouncedSave(app, () => channel?.postMessage('saved'));
+  const queueSave = saver.queue;
+  const changeListeners = new Set<(event: ChangeEvent) => void>();
+  app.onChange((event) => {
+    for (const listener of changeListeners) listener(event);
+  });
+  // A save queued here lands after the other tab's and wins, so only an
+  // idle tab takes the other tab's state.
+  channel?.addEventListener('message', () => {
+    if (saver.pending()) return;
+    void loadPersistedState().then((state) => {
+      if (saver.pending()) return;
+      try {
+        app.reloadState(JSON.stringify(state));
+      } catch (error) {
+        console.warn('Memory Pak: failed to reload state from another tab', error);
+      }
+    });
+  });
 
   return {
     loadInitialState: () => Promise.resolve(app.loadInitialState()),
     queryConsoles: (input) => Promise.resolve(app.queryConsoles(input)),
     queryGames: (input) => Promise.resolve(app.queryGames(input)),
     queryCollectibles: (input) => Promise.resolve(app.queryCollectibles(input)),
+    getItem: (id) => Promise.resolve(app.getItem(id)),
     setItemStatus: (input) => {
       const result = app.setItemStatus(input);
       queueSave();
@@ -52,19 +131,101 @@ export async function createWebWasmBackend(): Promise<MemoryPakBackend> {
       queueSave();
       return Promise.resolve(result);
     },
-    importJson: (json) => {
-      const stats = app.importJson(json);
+    setItemCopies: (input) => {
+      const result = app.setItemCopies(input);
+      queueSave();
+      return Promise.resolve(result);
+    },
+    setItemPlayStatus: (input) => {
+      const result = app.setItemPlayStatus(input);
+      queueSave();
+      return Promise.resolve(result);
+    },
+    setItemTags: (input) => {
+      const result = app.setItemTags(input);
       queueSave();
-      return Promise.resolve(stats);
+      return Promise.resolve(result);
+    },
+    setList: (input) => {
+      const list = app.setList(input);
+      queueSave();
+      return Promise.resolve(list);
+    },
+    renameList: (input) => {
+      const list = app.renameList(input);
+      queueSave();
+      return Promise.resolve(list);
+    },
+    deleteList: (name) => {
+      app.deleteList(name);
+      queueSave();
+      return Promise.resolve();
+    },
+    applyBatch: (changes) => {
+      const result = app.applyBatch(changes);
+      queueSave();
+      return Promise.resolve(result);
+    },
+    applyStatusToQuery: (input) => {
+      const result = app.applyStatusToQuery(input);
+      if (!result.dryRun && result.affected) queueSave();
+      return Promise.resolve(result);
+    },
+    undo: () => {
+      const result = app.undo() ?? null;
+      if (result) queueSave();
+      return Promise.resolve(result);
+    },
+    redo: () => {
+      const result = app.redo() ?? null;
+      if (result) queueSave();
+      return Promise.resolve(result);
+    },
+    canUndo: () => Promise.resolve(app.canUndo()),
+    canRedo: () => Promise.resolve(app.canRedo()),
+    previewImportJson: (json, options) => Promise.resolve(app.previewImportJson(json, options)),
+    importJson: (json, options) => {
+      const summary = app.importJson(json, options);
+      queueSave();
+      return Promise.resolve(summary);
     },
     exportJson: () => Promise.resolve(app.exportJson()),
+    importCsv: (csv, options) => {
+      const report = app.importCsv(csv, options);
+      queueSave();
+      return Promise.resolve(report);
+    },
+    exportCsv: () => Promise.resolve(app.exportCsv()),
     getCollectionStats: () => Promise.resolve(app.getCollectionStats()),
-    importFromFile: async () => {
-      const file = await pickJsonFile();
+    listOrphans: () => Promise.resolve(app.listOrphans()),
+    repairOrphans: (repairs) => {
+      const summary = app.repairOrphans(repairs);
+      queueSave();
+      return Promise.resolve(summary);
+    },
+    getSpendingReport: () => Promise.resolve(app.getSpendingReport()),
+    growthTimeline: (input) => Promise.resolve(app.growthTimeline(input)),
+    recentActivity: (query) => Promise.resolve(app.recentActivity(query)),
+    entryHistory: (id) => Promise.resolve(app.entryHistory(id)),
+    activityPolicy: () => Promise.resolve(app.activityPolicy() ?? null),
+    setActivityPolicy: (policy) => {
+      const dropped = app.setActivityPolicy(policy);
+      queueSave();
+      return Promise.resolve(dropped);
+    },
+    onStateChange: (listener) => {
+      changeListeners.add(listener);
+      return () => changeListeners.delete(listener);
+    },
+    importFromFile: async (options, csvOptions) => {
+      const file = await pickImportFile();
       if (!file) return undefined;
-      const stats = app.importJson(await file.text());
+      const text = await file.text();
+      const result: FileImportResult = file.name.toLowerCase().endsWith('.csv')
+        ? { format: 'csv', ...app.importCsv(text, csvOptions) }
+        : { format: 'json', ...app.importJson(text, options) };
       queueSave();
-      return stats;
+      return result;
     },
     exportToFile: async () => {
       downloadJson(app.exportJson(), 'memory_pak_export.json');
@@ -72,13 +233,19 @@ export async function createWebWasmBackend(): Promise<MemoryPakBackend> {
   };
 }
 
-function makeDebouncedSave(app: WasmInstance): () => void {
+interface DebouncedSave {
+  queue(): void;
+  /** Whether a save is waiting for the debounce. */
+  pending(): boolean;
+}
+
+function makeDebouncedSave(app: WasmInstance, onSaved: () => void): DebouncedSave {
   let timer: ReturnType<typeof setTimeout> | null = null;
 
   const flush = (): void => {
     timer = null;
     const snapshot = JSON.parse(app.snapshotStateJson()) as PersistedState;
-    void savePersistedState(snapshot);
+    void savePersistedState(snapshot).then(onSaved);
   };
 
   if (typeof window !== 'undefined') {
@@ -90,16 +257,19 @@ function makeDebouncedSave(app: WasmInstance): () => void {
     });
   }
 
-  return () => {
-    if (timer) clearTimeout(timer);
-    timer = setTimeout(flush, SAVE_DEBOUNCE_MS);
+  return {
+    queue: () => {
+      if (timer) clearTimeout(timer);
+      timer = setTimeout(flush, SAVE_DEBOUNCE_MS);
+    },
+    pending: () => timer !== null
   };
 }
 
-async function pickJsonFile(): Promise<File | undefined> {
+async function pickImportFile(): Promise<File | undefined> {
   const input = document.createElement('input');
   input.type = 'file';
-  input.accept = 'application/json,.json';
+  input.accept = 'application/json,.json,text/csv,.csv';
 
   return new Promise((resolve) => {
     input.addEventListener(
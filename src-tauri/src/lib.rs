@@ -1,23 +1,77 @@
-mod persistence;
-
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use memory_pak_core::{
-    CollectibleView, CollectionStats, ConsoleView, GameView, InitialState, MemoryPakApp,
-    MutationResult, QueryInput, QueryResult, SetItemNotesInput, SetItemStatusInput,
+    default_state_path, load_persisted_state, save_persisted_state, ActivityEvent, ActivityPolicy,
+    ActivityQuery, ActivityView, BatchChange, BatchResult, CollectibleView, CollectionStats,
+    ConsoleView, CsvImportOptions, EntryId, EntryList, GameView, HistoryResult, ImportOptions,
+    ImportPreview, ImportReport, ImportSummary, InitialState, ItemView, MemoryPakApp,
+    MutationResult, OrphanEntry, OrphanRepair, OrphanRepairSummary, PersistedState, QueryInput,
+    QueryPatchInput, QueryPatchResult, QueryResult, RenameListInput, SetItemCopiesInput,
+    SetItemNotesInput, SetItemPlayStatusInput, SetItemStatusInput, SetItemTagsInput, SetListInput,
+    SpendingReport, TimelineInput, TimelineReport,
 };
-use parking_lot::RwLock;
-use persistence::{load_persisted_state, save_persisted_state};
+use parking_lot::{Mutex, RwLock};
 use serde::Serialize;
-use tauri::State;
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
 
 struct AppState {
     app: RwLock<MemoryPakApp>,
+    /// Why `state.json` could not be read. While set, nothing is saved, so
+    /// the empty stand-in state never overwrites the file.
+    load_error: Option<String>,
+    /// Modification time of `state.json` as last read or written.
+    modified: Mutex<Option<SystemTime>>,
 }
 
+impl AppState {
+    /// Reloads `state.json` if another program, such as the CLI or
+    /// `memory-pak-server`, changed it since the app last read or wrote it.
+    /// Called before every mutation so the save after it keeps their changes.
+    fn refresh(&self, app: &mut MemoryPakApp) -> Result<(), String> {
+        if self.load_error.is_some() {
+            return Ok(());
+        }
+        let mut known = self.modified.lock();
+        let current = state_modified();
+        if current != *known {
+            app.reload_state(load_persisted_state().map_err(|err| err.to_string())?);
+            *known = current;
+        }
+        Ok(())
+    }
+
+    fn save(&self, app: &MemoryPakApp) -> Result<(), String> {
+        if let Some(err) = &self.load_error {
+            return Err(format!("Changes are not saved: {err}"));
+        }
+        save_persisted_state(app.persisted_state()).map_err(|err| err.to_string())?;
+        *self.modified.lock() = state_modified();
+        Ok(())
+    }
+}
+
+fn state_modified() -> Option<SystemTime> {
+    let path = default_state_path().ok().flatten()?;
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Carries a `ChangeEvent` after every command that changes the state.
+const STATE_CHANGED_EVENT: &str = "state-changed";
+
 const ANDROID_STORE_URL: &str =
     "https://play.google.com/store/apps/details?id=com.Aspenini.MemoryPak";
 
+/// What `import_from_path` did, tagged by the file's format.
+#[derive(Debug, Serialize)]
+#[serde(tag = "format", rename_all = "camelCase")]
+enum FileImport {
+    Csv(ImportReport),
+    Json(ImportSummary),
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct AndroidUpdateStatus {
@@ -43,6 +97,15 @@ fn query_games(input: QueryInput, state: State<'_, AppState>) -> QueryResult<Gam
     state.app.read().query_games(input)
 }
 
+#[tauri::command]
+fn get_item(id: EntryId, state: State<'_, AppState>) -> Result<ItemView, String> {
+    state
+        .app
+        .read()
+        .get_item(&id)
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 fn query_collectibles(
     input: QueryInput,
@@ -57,8 +120,9 @@ fn set_item_status(
     state: State<'_, AppState>,
 ) -> Result<MutationResult, String> {
     let mut app = state.app.write();
+    state.refresh(&mut app)?;
     let result = app.set_item_status(input).map_err(|err| err.to_string())?;
-    save_persisted_state(app.persisted_state()).map_err(|err| err.to_string())?;
+    state.save(&app)?;
     Ok(result)
 }
 
@@ -68,17 +132,164 @@ fn set_item_notes(
     state: State<'_, AppState>,
 ) -> Result<MutationResult, String> {
     let mut app = state.app.write();
+    state.refresh(&mut app)?;
     let result = app.set_item_notes(input).map_err(|err| err.to_string())?;
-    save_persisted_state(app.persisted_state()).map_err(|err| err.to_string())?;
+    state.save(&app)?;
+    Ok(result)
+}
+
+#[tauri::command]
+fn set_item_copies(
+    input: SetItemCopiesInput,
+    state: State<'_, AppState>,
+) -> Result<MutationResult, String> {
+    let mut app = state.app.write();
+    state.refresh(&mut app)?;
+    let result = app.set_item_copies(input).map_err(|err| err.to_string())?;
+    state.save(&app)?;
+    Ok(result)
+}
+
+#[tauri::command]
+fn set_item_play_status(
+    input: SetItemPlayStatusInput,
+    state: State<'_, AppState>,
+) -> Result<MutationResult, String> {
+    let mut app = state.app.write();
+    state.refresh(&mut app)?;
+    let result = app
+        .set_item_play_status(input)
+        .map_err(|err| err.to_string())?;
+    state.save(&app)?;
+    Ok(result)
+}
+
+#[tauri::command]
+fn set_item_tags(
+    input: SetItemTagsInput,
+    state: State<'_, AppState>,
+) -> Result<MutationResult, String> {
+    let mut app = state.app.write();
+    state.refresh(&mut app)?;
+    let result = app.set_item_tags(input).map_err(|err| err.to_string())?;
+    state.save(&app)?;
+    Ok(result)
+}
+
+#[tauri::command]
+fn set_list(input: SetListInput, state: State<'_, AppState>) -> Result<EntryList, String> {
+    let mut app = state.app.write();
+    state.refresh(&mut app)?;
+    let result = app.set_list(input).map_err(|err| err.to_string())?;
+    state.save(&app)?;
+    Ok(result)
+}
+
+#[tauri::command]
+fn rename_list(input: RenameListInput, state: State<'_, AppState>) -> Result<EntryList, String> {
+    let mut app = state.app.write();
+    state.refresh(&mut app)?;
+    let result = app.rename_list(input).map_err(|err| err.to_string())?;
+    state.save(&app)?;
+    Ok(result)
+}
+
+#[tauri::command]
+fn delete_list(name: String, state: State<'_, AppState>) -> Result<(), String> {
+    let mut app = state.app.write();
+    state.refresh(&mut app)?;
+    app.delete_list(&name).map_err(|err| err.to_string())?;
+    state.save(&app)
+}
+
+/// All or nothing, with a single save for the whole batch.
+#[tauri::command]
+fn apply_batch(
+    changes: Vec<BatchChange>,
+    state: State<'_, AppState>,
+) -> Result<BatchResult, String> {
+    let mut app = state.app.write();
+    state.refresh(&mut app)?;
+    let result = app.apply_batch(changes).map_err(|err| err.to_string())?;
+    state.save(&app)?;
+    Ok(result)
+}
+
+/// Saves only when the patch changed something.
+#[tauri::command]
+fn apply_status_to_query(
+    input: QueryPatchInput,
+    state: State<'_, AppState>,
+) -> Result<QueryPatchResult, String> {
+    let mut app = state.app.write();
+    state.refresh(&mut app)?;
+    let result = app
+        .apply_status_to_query(input)
+        .map_err(|err| err.to_string())?;
+    if !result.dry_run && result.affected > 0 {
+        state.save(&app)?;
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+fn undo(state: State<'_, AppState>) -> Result<Option<HistoryResult>, String> {
+    let mut app = state.app.write();
+    state.refresh(&mut app)?;
+    let result = app.undo();
+    if result.is_some() {
+        state.save(&app)?;
+    }
     Ok(result)
 }
 
 #[tauri::command]
-fn import_json(json: String, state: State<'_, AppState>) -> Result<CollectionStats, String> {
+fn redo(state: State<'_, AppState>) -> Result<Option<HistoryResult>, String> {
+    let mut app = state.app.write();
+    state.refresh(&mut app)?;
+    let result = app.redo();
+    if result.is_some() {
+        state.save(&app)?;
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+fn can_undo(state: State<'_, AppState>) -> bool {
+    state.app.read().can_undo()
+}
+
+#[tauri::command]
+fn can_redo(state: State<'_, AppState>) -> bool {
+    state.app.read().can_redo()
+}
+
+#[tauri::command]
+fn preview_import_json(
+    json: String,
+    options: Option<ImportOptions>,
+    state: State<'_, AppState>,
+) -> Result<ImportPreview, String> {
+    state
+        .app
+        .read()
+        .preview_import_json(&json, options.unwrap_or_default())
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn import_json(
+    json: String,
+    options: Option<ImportOptions>,
+    state: State<'_, AppState>,
+) -> Result<ImportSummary, String> {
     let mut app = state.app.write();
-    let stats = app.import_json(&json).map_err(|err| err.to_string())?;
-    save_persisted_state(app.persisted_state()).map_err(|err| err.to_string())?;
-    Ok(stats)
+    state.refresh(&mut app)?;
+    let summary = app
+        .import_json(&json, options.unwrap_or_default())
+        .map_err(|err| err.to_string())?;
+    state.save(&app)?;
+    Ok(summary)
 }
 
 #[tauri::command]
@@ -96,15 +307,119 @@ fn get_collection_stats(state: State<'_, AppState>) -> CollectionStats {
 }
 
 #[tauri::command]
-fn import_from_path(path: String, state: State<'_, AppState>) -> Result<CollectionStats, String> {
-    let json = std::fs::read_to_string(PathBuf::from(path)).map_err(|err| err.to_string())?;
-    import_json(json, state)
+fn list_orphans(state: State<'_, AppState>) -> Vec<OrphanEntry> {
+    state.app.read().orphans()
+}
+
+#[tauri::command]
+fn repair_orphans(
+    repairs: Vec<OrphanRepair>,
+    state: State<'_, AppState>,
+) -> Result<OrphanRepairSummary, String> {
+    let mut app = state.app.write();
+    state.refresh(&mut app)?;
+    let summary = app.repair_orphans(repairs).map_err(|err| err.to_string())?;
+    state.save(&app)?;
+    Ok(summary)
+}
+
+#[tauri::command]
+fn get_spending_report(state: State<'_, AppState>) -> SpendingReport {
+    state.app.read().spending_report()
 }
 
+#[tauri::command]
+fn growth_timeline(
+    input: Option<TimelineInput>,
+    state: State<'_, AppState>,
+) -> Result<TimelineReport, String> {
+    state
+        .app
+        .read()
+        .growth_timeline(input.unwrap_or_default())
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn recent_activity(query: Option<ActivityQuery>, state: State<'_, AppState>) -> Vec<ActivityView> {
+    state.app.read().recent_activity(query.unwrap_or_default())
+}
+
+#[tauri::command]
+fn entry_history(id: EntryId, state: State<'_, AppState>) -> Vec<ActivityEvent> {
+    state.app.read().entry_history(&id)
+}
+
+#[tauri::command]
+fn activity_policy(state: State<'_, AppState>) -> Option<ActivityPolicy> {
+    state.app.read().activity_policy()
+}
+
+/// Returns how many events the new policy dropped.
+#[tauri::command]
+fn set_activity_policy(
+    policy: Option<ActivityPolicy>,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let mut app = state.app.write();
+    state.refresh(&mut app)?;
+    let dropped = app.set_activity_policy(policy);
+    state.save(&app)?;
+    Ok(dropped)
+}
+
+#[tauri::command]
+fn import_csv(
+    csv: String,
+    options: Option<CsvImportOptions>,
+    state: State<'_, AppState>,
+) -> Result<ImportReport, String> {
+    let mut app = state.app.write();
+    state.refresh(&mut app)?;
+    let report = app
+        .import_csv(&csv, options.unwrap_or_default())
+        .map_err(|err| err.to_string())?;
+    state.save(&app)?;
+    Ok(report)
+}
+
+#[tauri::command]
+fn export_csv(state: State<'_, AppState>) -> String {
+    state.app.read().export_csv()
+}
+
+/// Imports a `.csv` with `csv_options` or a JSON export with `options`.
+#[tauri::command]
+fn import_from_path(
+    path: String,
+    options: Option<ImportOptions>,
+    csv_options: Option<CsvImportOptions>,
+    state: State<'_, AppState>,
+) -> Result<FileImport, String> {
+    let path = PathBuf::from(path);
+    let contents = std::fs::read_to_string(&path).map_err(|err| err.to_string())?;
+    if is_csv(&path) {
+        import_csv(contents, csv_options, state).map(FileImport::Csv)
+    } else {
+        import_json(contents, options, state).map(FileImport::Json)
+    }
+}
+
+/// Writes CSV when `path` ends in `.csv` and the JSON export otherwise.
 #[tauri::command]
 fn export_to_path(path: String, state: State<'_, AppState>) -> Result<(), String> {
-    let json = export_json(state)?;
-    std::fs::write(PathBuf::from(path), json).map_err(|err| err.to_string())
+    let path = PathBuf::from(path);
+    let contents = if is_csv(&path) {
+        export_csv(state)
+    } else {
+        export_json(state)?
+    };
+    std::fs::write(path, contents).map_err(|err| err.to_string())
+}
+
+fn is_csv(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
 }
 
 #[tauri::command]
@@ -130,26 +445,53 @@ fn android_open_update_target() -> String {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let state = load_persisted_state().unwrap_or_default();
+    let (state, load_error) = match load_persisted_state() {
+        Ok(state) => (state, None),
+        Err(err) => (PersistedState::default(), Some(err.to_string())),
+    };
     let app = MemoryPakApp::from_persisted_state(state);
 
     tauri::Builder::default()
         .manage(AppState {
             app: RwLock::new(app),
+            load_error,
+            modified: Mutex::new(state_modified()),
         })
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_process::init())
-        .setup(|_app| {
+        .setup(|app| {
+            // A state the app cannot read, such as one written by a newer
+            // build, is left untouched: say why and close.
+            if let Some(err) = &app.state::<AppState>().load_error {
+                let handle = app.handle().clone();
+                app.dialog()
+                    .message(format!(
+                        "Your saved collection could not be read, so Memory Pak will close \
+                         without changing it.\n\n{err}"
+                    ))
+                    .title("Memory Pak")
+                    .kind(MessageDialogKind::Error)
+                    .show(move |_| handle.exit(1));
+                return Ok(());
+            }
+
+            // Every window gets every change, so they stay in sync without
+            // re-querying.
+            let handle = app.handle().clone();
+            app.state::<AppState>().app.write().subscribe(move |event| {
+                let _ = handle.emit(STATE_CHANGED_EVENT, event);
+            });
+
             #[cfg(desktop)]
-            if _app
+            if app
                 .config()
                 .plugins
                 .0
                 .get("updater")
                 .is_some_and(|config| !config.is_null())
             {
-                _app.handle()
+                app.handle()
                     .plugin(tauri_plugin_updater::Builder::new().build())?;
             }
             Ok(())
@@ -159,11 +501,35 @@ pub fn run() {
             query_consoles,
             query_games,
             query_collectibles,
+            get_item,
             set_item_status,
             set_item_notes,
+            set_item_copies,
+            set_item_play_status,
+            set_item_tags,
+            set_list,
+            rename_list,
+            delete_list,
+            apply_batch,
+            apply_status_to_query,
+            undo,
+            redo,
+            can_undo,
+            can_redo,
+            preview_import_json,
             import_json,
             export_json,
+            import_csv,
+            export_csv,
             get_collection_stats,
+            list_orphans,
+            repair_orphans,
+            get_spending_report,
+            growth_timeline,
+            recent_activity,
+            entry_history,
+            activity_policy,
+            set_activity_policy,
             import_from_path,
             export_to_path,
             android_check_store_update,